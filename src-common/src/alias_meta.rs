@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use psh::{CharSet, Psh, ZeroizingString};
+
+use crate::{Passphrase, Secret, Username};
+
+// Alias which derived password keys encryption of alias settings.
+// It's never stored in Psh database.
+const KEY_ALIAS: &str = "\u{1}psh-gui:alias-settings";
+const NONCE_LEN: usize = 12;
+
+/// Alias settings that Psh has no place for.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AliasMeta {
+    // Length of derived password (full length if not set)
+    #[serde(rename = "l", skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+    // Number of times alias password was rotated
+    #[serde(rename = "g", skip_serializing_if = "is_zero")]
+    pub generation: u32,
+    // The latest generation alias ever had
+    #[serde(rename = "m", skip_serializing_if = "is_zero")]
+    pub max_generation: u32,
+    // Words to derive instead of characters
+    #[serde(rename = "w", skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<Passphrase>,
+    // Login used on the site
+    #[serde(rename = "u", skip_serializing_if = "Option::is_none")]
+    pub username: Option<Username>,
+    // Password stored instead of derived one (e.g. issued by site)
+    #[serde(rename = "p", skip_serializing_if = "Option::is_none")]
+    pub password: Option<Secret>,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+/// Settings of all the aliases. They are kept apart from Psh database in a single
+/// record encrypted with a key derived from master password, so other Psh clients
+/// (and backups made with them) only ever see real aliases.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetaStore {
+    metas: HashMap<String, AliasMeta>,
+}
+
+impl MetaStore {
+    /// Decrypts settings record (if any) with key derived by given Psh.
+    pub fn load(psh: &Psh, record: Option<&str>) -> anyhow::Result<Self> {
+        let metas = match record {
+            Some(record) => serde_json::from_slice(&open(&key(psh), record)?)?,
+            None => HashMap::new(),
+        };
+        Ok(Self { metas })
+    }
+
+    /// Encrypts settings with key derived by given Psh (which may be unlocked
+    /// with another master password, e.g. on re-encryption).
+    pub fn seal(&self, psh: &Psh) -> anyhow::Result<String> {
+        let json = Zeroizing::new(serde_json::to_vec(&self.metas)?);
+        seal(&key(psh), &json)
+    }
+
+    pub fn get(&self, alias: &str) -> AliasMeta {
+        self.metas.get(alias).cloned().unwrap_or_default()
+    }
+
+    /// Sets alias settings. Default settings aren't stored at all.
+    pub fn set(&mut self, alias: &str, meta: AliasMeta) {
+        if meta == AliasMeta::default() {
+            self.metas.remove(alias);
+        } else {
+            self.metas.insert(alias.to_string(), meta);
+        }
+    }
+}
+
+fn key(psh: &Psh) -> Zeroizing<[u8; 32]> {
+    let derived = psh.derive_password(
+        &ZeroizingString::new(KEY_ALIAS.to_string()),
+        None,
+        Some(CharSet::Standard),
+    );
+    Zeroizing::new(Sha256::digest(derived.as_bytes()).into())
+}
+
+// Encrypts data into base64 record prefixed with random nonce
+fn seal(key: &[u8; 32], data: &[u8]) -> anyhow::Result<String> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let encrypted = cipher.encrypt(&nonce, data)
+        .map_err(|_| anyhow!("Failed to encrypt alias settings"))?;
+    let mut record = nonce.to_vec();
+    record.extend(encrypted);
+    Ok(BASE64.encode(record))
+}
+
+fn open(key: &[u8; 32], record: &str) -> anyhow::Result<Zeroizing<Vec<u8>>> {
+    let record = BASE64.decode(record)?;
+    if record.len() < NONCE_LEN {
+        bail!("Alias settings record is malformed");
+    }
+    let (nonce, encrypted) = record.split_at(NONCE_LEN);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    cipher.decrypt(Nonce::from_slice(nonce), encrypted)
+        .map(Zeroizing::new)
+        .map_err(|_| anyhow!("Failed to decrypt alias settings"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_sealed_record() {
+        let key = [7; 32];
+        let record = seal(&key, b"{}").unwrap();
+        assert_eq!(open(&key, &record).unwrap().as_slice(), b"{}");
+        // Every record gets its own nonce
+        assert_ne!(seal(&key, b"{}").unwrap(), record);
+    }
+
+    #[test]
+    fn rejects_wrong_key_and_malformed_record() {
+        let record = seal(&[7; 32], b"{}").unwrap();
+        assert!(open(&[8; 32], &record).is_err());
+        assert!(open(&[7; 32], "AAAA").is_err());
+        assert!(open(&[7; 32], "not base64!").is_err());
+    }
+
+    #[test]
+    fn drops_default_settings() {
+        let mut store = MetaStore::default();
+        let meta = AliasMeta { generation: 2, ..Default::default() };
+        store.set("alias", meta.clone());
+        assert_eq!(store.get("alias"), meta);
+        store.set("alias", AliasMeta::default());
+        assert_eq!(store, MetaStore::default());
+    }
+}
@@ -0,0 +1,206 @@
+//! Alias operations on unlocked Psh that keep alias settings in sync with
+//! aliases themselves.
+
+use std::collections::HashMap;
+
+use psh::{CharSet, Psh, ZeroizingString};
+
+use crate::alias_meta::{AliasMeta, MetaStore};
+use crate::password::{generation_alias, shape_password, to_username, username_alias};
+use crate::{charset_by_id, charset_id, psh_secret, AliasInfo, Passphrase, PasswordChange, Secret};
+
+pub fn aliases(psh: &Psh, metas: &MetaStore) -> Vec<AliasInfo> {
+    alias_infos(psh, metas, false)
+}
+
+/// Aliases along with their stored passwords (to be moved between databases).
+pub fn aliases_with_passwords(psh: &Psh, metas: &MetaStore) -> Vec<AliasInfo> {
+    alias_infos(psh, metas, true)
+}
+
+fn alias_infos(psh: &Psh, metas: &MetaStore, with_passwords: bool) -> Vec<AliasInfo> {
+    psh.aliases()
+        .iter()
+        .map(|alias| {
+            let meta = metas.get(alias);
+            AliasInfo {
+                alias: alias.to_string(),
+                use_secret: psh.alias_uses_secret(alias),
+                charset: charset_id(psh.get_charset(alias)),
+                length: meta.length,
+                generation: meta.generation,
+                max_generation: meta.max_generation.max(meta.generation),
+                passphrase: meta.passphrase,
+                username: meta.username,
+                stored: meta.password.is_some(),
+                password: meta.password.filter(|_| with_passwords),
+            }
+        })
+        .collect()
+}
+
+/// Stores alias in psh database along with settings Psh has no place for.
+pub fn append_alias(psh: &mut Psh, metas: &mut MetaStore, info: AliasInfo) -> anyhow::Result<()> {
+    psh.append_alias_to_db(
+        &ZeroizingString::new(info.alias.clone()),
+        Some(info.use_secret),
+        Some(charset_by_id(info.charset)?),
+    )?;
+    let meta = AliasMeta {
+        length: info.length,
+        generation: info.generation,
+        max_generation: info.max_generation.max(info.generation),
+        passphrase: info.passphrase,
+        username: info.username,
+        password: info.password,
+    };
+    metas.set(&info.alias, meta);
+    Ok(())
+}
+
+pub fn remove_alias(psh: &mut Psh, metas: &mut MetaStore, alias: String) -> anyhow::Result<()> {
+    psh.remove_alias_from_db(&ZeroizingString::new(alias.clone()))?;
+    metas.set(&alias, AliasMeta::default());
+    Ok(())
+}
+
+/// Replaces alias with another one (possibly with the same name but different settings).
+/// Psh has no means to modify alias so we remove it and append the new one.
+/// New alias is checked beforehand and old one is restored if the new one can't be appended
+/// anyway.
+pub fn update_alias(psh: &mut Psh, metas: &mut MetaStore, alias: String, mut new: AliasInfo) -> anyhow::Result<()> {
+    charset_by_id(new.charset)?;
+    if new.alias.trim().is_empty() {
+        anyhow::bail!("Alias can't be empty");
+    }
+    let current = aliases_with_passwords(psh, metas);
+    if new.alias != alias && current.iter().any(|info| info.alias == new.alias) {
+        anyhow::bail!("Alias {} already exists", new.alias);
+    }
+    let old = current.into_iter().find(|info| info.alias == alias);
+    if new.stored && new.password.is_none() {
+        // Stored password is kept unless replaced
+        new.password = old.as_ref().and_then(|old| old.password.clone());
+    }
+    remove_alias(psh, metas, alias)?;
+    if let Err(e) = append_alias(psh, metas, new) {
+        if let Some(old) = old {
+            append_alias(psh, metas, old)
+                .map_err(|restore_e| anyhow::anyhow!("{}; old alias couldn't be restored either: {}", e, restore_e))?;
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Adds imported aliases and replaces conflicting ones with imported ones.
+/// Stored passwords are taken from `passwords` kept since import preview.
+pub fn apply_import(
+    psh: &mut Psh,
+    metas: &mut MetaStore,
+    new: Vec<AliasInfo>,
+    replace: Vec<AliasInfo>,
+    passwords: &mut HashMap<String, Secret>,
+) -> anyhow::Result<()> {
+    let mut with_password = |mut info: AliasInfo| {
+        if info.stored {
+            info.password = passwords.remove(&info.alias);
+        }
+        info
+    };
+    for info in new {
+        append_alias(psh, metas, with_password(info))?;
+    }
+    for info in replace {
+        update_alias(psh, metas, info.alias.clone(), with_password(info))?;
+    }
+    Ok(())
+}
+
+/// Stores password of alias adding the alias if it doesn't exist.
+pub fn store_password(psh: &mut Psh, metas: &mut MetaStore, alias: String, password: Secret) -> anyhow::Result<()> {
+    let password = Some(password);
+    match aliases(psh, metas).into_iter().find(|info| info.alias == alias) {
+        Some(info) => {
+            let new = AliasInfo { stored: true, password, ..info };
+            update_alias(psh, metas, alias, new)
+        }
+        None => append_alias(psh, metas, AliasInfo {
+            alias,
+            stored: true,
+            password,
+            ..Default::default()
+        }),
+    }
+}
+
+/// Returns password of alias: either stored one or the one derived for
+/// given generation and shaped as alias settings say.
+#[allow(clippy::too_many_arguments)]
+pub fn password(
+    psh: &Psh,
+    metas: &MetaStore,
+    alias: &str,
+    secret: Option<Secret>,
+    charset: CharSet,
+    length: Option<usize>,
+    generation: u32,
+    passphrase: Option<&Passphrase>,
+) -> Secret {
+    if let Some(password) = metas.get(alias).password {
+        return password;
+    }
+    let pass = psh.derive_password(
+        &ZeroizingString::new(generation_alias(alias, generation)),
+        secret.map(psh_secret),
+        Some(charset),
+    );
+    Secret::new(shape_password(&pass, length, passphrase))
+}
+
+/// Derives login for alias, so that accounts on different sites can't be
+/// linked by username.
+pub fn username(psh: &Psh, alias: &str) -> String {
+    let derived = psh.derive_password(
+        &ZeroizingString::new(username_alias(alias)),
+        None,
+        Some(CharSet::Reduced),
+    );
+    to_username(&derived)
+}
+
+/// Copies all the aliases to Psh of another database, e.g. to re-encrypt them
+/// with another master password. Their settings are to be sealed with the new Psh.
+pub fn copy_aliases(psh: &Psh, new_psh: &mut Psh) -> anyhow::Result<()> {
+    for alias in psh.aliases() {
+        new_psh.append_alias_to_db(
+            alias,
+            Some(psh.alias_uses_secret(alias)),
+            Some(psh.get_charset(alias)),
+        )?;
+    }
+    Ok(())
+}
+
+/// Derives passwords of aliases that don't use secret with both old and new master passwords.
+pub fn password_changes(old_psh: &Psh, new_psh: &Psh, metas: &MetaStore) -> anyhow::Result<Vec<PasswordChange>> {
+    aliases(old_psh, metas)
+        .into_iter()
+        // Stored passwords don't depend on master password
+        .filter(|info| !info.stored)
+        .map(|info| {
+            let (old_password, new_password) = if info.use_secret {
+                (None, None)
+            } else {
+                let alias = ZeroizingString::new(generation_alias(&info.alias, info.generation));
+                let charset = Some(charset_by_id(info.charset)?);
+                let derive = |psh: &Psh| {
+                    let pass = psh.derive_password(&alias, None, charset);
+                    Secret::new(shape_password(&pass, info.length, info.passphrase.as_ref()))
+                };
+                (Some(derive(old_psh)), Some(derive(new_psh)))
+            };
+            Ok(PasswordChange { alias: info.alias, old_password, new_password })
+        })
+        .collect()
+}
@@ -0,0 +1,286 @@
+//! Psh GUI code shared by Web Worker and Tauri backends: alias settings,
+//! password shaping and types both backends exchange with the frontend.
+
+use std::collections::HashMap;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+use psh::{CharSet, ZeroizingString};
+use zeroize::Zeroizing;
+
+pub mod alias_meta;
+pub mod aliases;
+pub mod password;
+
+/// Sensitive string (master password, derived password, etc.) that is wiped
+/// from memory once dropped. Unlike `ZeroizingString` it can be sent between
+/// frontend and backend, so secrets don't pass through plain strings.
+pub type Secret = Zeroizing<String>;
+
+/// Hands secret over to Psh without leaving a copy behind.
+pub fn psh_secret(mut secret: Secret) -> ZeroizingString {
+    ZeroizingString::new(std::mem::take(&mut *secret))
+}
+
+pub fn charset_id(charset: CharSet) -> usize {
+    match charset {
+        CharSet::Standard => 0,
+        CharSet::RequireAll => 1,
+        CharSet::Reduced => 2,
+    }
+}
+
+pub fn charset_by_id(id: usize) -> anyhow::Result<CharSet> {
+    match id {
+        0 => Ok(CharSet::Standard),
+        1 => Ok(CharSet::RequireAll),
+        2 => Ok(CharSet::Reduced),
+        _ => bail!("Unknown charset: {}", id),
+    }
+}
+
+/// Compares aliases from backup file with the ones stored in psh database.
+pub fn import_preview(current: &[AliasInfo], imported: Vec<AliasInfo>) -> ImportPreview {
+    let mut preview = ImportPreview { new: Vec::new(), conflicts: Vec::new() };
+    for info in imported {
+        match current.iter().find(|a| a.alias == info.alias) {
+            None => preview.new.push(info),
+            Some(current) if *current != info => {
+                preview.conflicts.push(ImportConflict { current: current.clone(), imported: info });
+            }
+            // Identical alias
+            _ => {}
+        }
+    }
+    preview
+}
+
+// Psh stores every record as base64 string
+fn is_base64(s: &str) -> bool {
+    let data = s.trim_end_matches('=');
+    !s.is_empty()
+        && s.len().is_multiple_of(4)
+        && s.len() - data.len() <= 2
+        && data.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '-' | '_'))
+}
+
+/// Checks that psh database records can be parsed, so that unlock failure
+/// can only mean wrong master password.
+pub fn check_records(records: &[String]) -> Result<(), UnlockError> {
+    match records.iter().position(|r| !is_base64(r)) {
+        Some(i) => Err(UnlockError::CorruptDb(format!("Record {} is malformed", i + 1))),
+        None => Ok(()),
+    }
+}
+
+// Alias that is stored in psh database along with its settings
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AliasInfo {
+    pub alias: String,
+    pub use_secret: bool,
+    pub charset: usize,
+    // Length of derived password (full length if not set)
+    #[serde(default)]
+    pub length: Option<usize>,
+    // Number of times alias password was rotated
+    #[serde(default)]
+    pub generation: u32,
+    // The latest generation alias ever had (current one may be rolled back from it)
+    #[serde(default)]
+    pub max_generation: u32,
+    // Words to derive instead of characters
+    #[serde(default)]
+    pub passphrase: Option<Passphrase>,
+    // Login used on the site
+    #[serde(default)]
+    pub username: Option<Username>,
+    // Whether alias holds stored password instead of derived one
+    #[serde(default)]
+    pub stored: bool,
+    // Stored password itself. It never leaves backend: alias lists and import
+    // previews don't carry it, it's only sent on explicit reveal or copy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<Secret>,
+}
+
+impl AliasInfo {
+    /// Alias switched to a new password generation. Generations rolled back from
+    /// are never reused, as their passwords may be compromised.
+    pub fn rotated(&self) -> Self {
+        let generation = self.max_generation.max(self.generation) + 1;
+        Self { generation, max_generation: generation, ..self.clone() }
+    }
+
+    /// Alias switched back to the previous password generation.
+    pub fn rolled_back(&self) -> Self {
+        Self { generation: self.generation.saturating_sub(1), ..self.clone() }
+    }
+}
+
+// Passphrase settings of an alias
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Passphrase {
+    pub words: usize,
+    pub separator: String,
+}
+
+// Username of an alias
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Username {
+    // Stored as is
+    Plain(String),
+    // Derived from alias with master password, so it can't be linked to other sites
+    Derived,
+}
+
+// Passwords of an alias derived with old and new master passwords
+// (not available for aliases that use secret)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PasswordChange {
+    pub alias: String,
+    pub old_password: Option<Secret>,
+    pub new_password: Option<Secret>,
+}
+
+// Alias stored both in psh database and backup file but with different settings
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImportConflict {
+    pub current: AliasInfo,
+    pub imported: AliasInfo,
+}
+
+// Result of backup file reading
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImportPreview {
+    // Aliases that are absent in psh database
+    pub new: Vec<AliasInfo>,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+impl ImportPreview {
+    /// Takes stored passwords out of the preview, so that they stay in backend
+    /// until import is applied. Returns passwords of imported aliases.
+    pub fn take_passwords(&mut self) -> HashMap<String, Secret> {
+        let mut passwords = HashMap::new();
+        let imported = self.new.iter_mut().chain(self.conflicts.iter_mut().map(|c| &mut c.imported));
+        for info in imported {
+            if let Some(password) = info.password.take() {
+                passwords.insert(info.alias.clone(), password);
+            }
+        }
+        for conflict in &mut self.conflicts {
+            conflict.current.password = None;
+        }
+        passwords
+    }
+}
+
+// Backup file contents. Records are encrypted with either master password or
+// separate export passphrase.
+#[derive(Serialize, Deserialize)]
+pub struct BackupFile {
+    pub version: u32,
+    pub records: Vec<String>,
+    // Alias settings sealed with the same password as records
+    pub meta: String,
+}
+
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Version of psh database layout. Databases of newer versions are refused.
+pub const DB_VERSION: u32 = 1;
+
+// Reason why psh couldn't be unlocked
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum UnlockError {
+    WrongPassword,
+    // Database records can't be parsed
+    CorruptDb(String),
+    // Database can't be read or written (e.g. storage is full or access is denied)
+    StorageFailure(String),
+    // Database was written by a newer app version
+    UnsupportedDbVersion(u32),
+    Other(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_charset_ids() {
+        for charset in [CharSet::Standard, CharSet::RequireAll, CharSet::Reduced] {
+            assert_eq!(charset_by_id(charset_id(charset)).unwrap(), charset);
+        }
+        assert!(charset_by_id(3).is_err());
+    }
+
+    #[test]
+    fn checks_base64() {
+        let cases = [
+            ("", false),
+            ("YWJj", true),
+            ("YWI=", true),
+            ("YQ==", true),
+            ("YQ===", false),
+            ("YWJ", false),
+            ("YW-_", true),
+            ("YW J", false),
+            ("{\"a\"", false),
+        ];
+        for (s, valid) in cases {
+            assert_eq!(is_base64(s), valid, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn reports_malformed_record() {
+        assert_eq!(check_records(&[]), Ok(()));
+        assert_eq!(check_records(&["YWJj".to_string(), "YQ==".to_string()]), Ok(()));
+        assert_eq!(
+            check_records(&["YWJj".to_string(), "oop!".to_string(), "".to_string()]),
+            Err(UnlockError::CorruptDb("Record 2 is malformed".to_string())),
+        );
+    }
+
+    #[test]
+    fn rotates_to_unused_generations() {
+        let info = AliasInfo { alias: "alias".to_string(), ..Default::default() };
+        let info = info.rotated();
+        assert_eq!((info.generation, info.max_generation), (1, 1));
+        let info = info.rolled_back();
+        assert_eq!((info.generation, info.max_generation), (0, 1));
+        let info = info.rotated();
+        assert_eq!((info.generation, info.max_generation), (2, 2));
+        let info = info.rolled_back().rolled_back().rolled_back();
+        assert_eq!((info.generation, info.max_generation), (0, 2));
+    }
+
+    #[test]
+    fn previews_import() {
+        let alias = |name: &str, length| AliasInfo { alias: name.to_string(), length, ..Default::default() };
+        let current = [alias("a", None), alias("b", None)];
+        let preview = import_preview(&current, vec![alias("a", None), alias("b", Some(8)), alias("c", None)]);
+        assert_eq!(preview.new, vec![alias("c", None)]);
+        assert_eq!(preview.conflicts, vec![ImportConflict { current: alias("b", None), imported: alias("b", Some(8)) }]);
+    }
+
+    #[test]
+    fn takes_passwords_out_of_import_preview() {
+        let stored = |name: &str, password: &str| AliasInfo {
+            alias: name.to_string(),
+            stored: true,
+            password: Some(Secret::new(password.to_string())),
+            ..Default::default()
+        };
+        let current = [stored("a", "old")];
+        let mut preview = import_preview(&current, vec![stored("a", "new"), stored("b", "pass")]);
+        let passwords = preview.take_passwords();
+        assert_eq!(passwords.len(), 2);
+        assert_eq!(passwords["a"].as_str(), "new");
+        assert_eq!(passwords["b"].as_str(), "pass");
+        assert!(preview.new.iter().all(|info| info.password.is_none()));
+        assert!(preview.conflicts.iter().all(|c| c.current.password.is_none() && c.imported.password.is_none()));
+    }
+}
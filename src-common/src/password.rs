@@ -0,0 +1,186 @@
+//! Turning passwords derived by Psh into the ones aliases use.
+
+use crate::Passphrase;
+
+// EFF large wordlist (https://www.eff.org/dice), one word per line
+const WORDLIST: &str = include_str!("eff-large-wordlist.txt");
+
+// Length of derived usernames
+const USERNAME_LEN: usize = 12;
+
+// Character classes (lowercase, uppercase, digits, symbols) present in password
+fn char_classes(chars: &[char]) -> [bool; 4] {
+    [
+        chars.iter().any(char::is_ascii_lowercase),
+        chars.iter().any(char::is_ascii_uppercase),
+        chars.iter().any(char::is_ascii_digit),
+        chars.iter().any(|c| !c.is_ascii_alphanumeric()),
+    ]
+}
+
+/// Cuts derived password down to given length. The first run of characters
+/// that has all the character classes of the full password is taken, so that
+/// passwords of "Require All" charset keep meeting their requirements.
+pub fn fit_length(password: &str, length: Option<usize>) -> String {
+    let chars: Vec<char> = password.chars().collect();
+    match length {
+        Some(length) if length > 0 && length < chars.len() => {
+            let classes = char_classes(&chars);
+            chars.windows(length)
+                .find(|window| char_classes(window) == classes)
+                .unwrap_or(&chars[..length])
+                .iter()
+                .collect()
+        }
+        _ => password.to_string(),
+    }
+}
+
+/// Number of words password of given length can be mapped onto. Every character
+/// holds about 6.57 bits while every word takes about 12.9 bits, and words past
+/// that would only be padding.
+pub fn max_words(password_len: usize) -> usize {
+    let words = WORDLIST.lines().count() as f64;
+    (password_len as f64 * 95f64.log2() / words.log2()) as usize
+}
+
+/// Maps derived password onto words of bundled wordlist. Password is read as
+/// a big number (one digit per printable ASCII character) that is divided by
+/// wordlist size over and over, remainders being indices of words. Passphrase
+/// has no more than [`max_words`] words.
+pub fn to_passphrase(password: &str, passphrase: &Passphrase) -> String {
+    let words: Vec<&str> = WORDLIST.lines().collect();
+    let n = words.len() as u32;
+    let mut digits: Vec<u32> = password.chars()
+        .map(|c| (c as u32).saturating_sub(0x20) % 95)
+        .collect();
+    let count = passphrase.words.min(max_words(digits.len()));
+    let mut picked = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut rem = 0;
+        for digit in digits.iter_mut() {
+            let value = rem * 95 + *digit;
+            *digit = value / n;
+            rem = value % n;
+        }
+        picked.push(words[rem as usize]);
+    }
+    picked.join(&passphrase.separator)
+}
+
+/// Turns derived password into the one alias uses: either passphrase or
+/// password of given length.
+pub fn shape_password(password: &str, length: Option<usize>, passphrase: Option<&Passphrase>) -> String {
+    match passphrase {
+        Some(passphrase) => to_passphrase(password, passphrase),
+        None => fit_length(password, length),
+    }
+}
+
+/// Alias that password of given generation is derived for. The first generation
+/// uses alias itself and the later ones append "#<generation>" to it, so they can
+/// be reproduced with psh CLI as well.
+pub fn generation_alias(alias: &str, generation: u32) -> String {
+    if generation == 0 {
+        alias.to_string()
+    } else {
+        format!("{}#{}", alias, generation + 1)
+    }
+}
+
+/// Alias that username is derived for.
+pub fn username_alias(alias: &str) -> String {
+    format!("{}@username", alias)
+}
+
+/// Turns password derived for `username_alias` into a login most sites accept:
+/// lowercase letters and digits starting with a letter.
+pub fn to_username(derived: &str) -> String {
+    let chars = derived.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .skip_while(char::is_ascii_digit);
+    chars.take(USERNAME_LEN).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passphrase(words: usize, separator: &str) -> Passphrase {
+        Passphrase { words, separator: separator.to_string() }
+    }
+
+    #[test]
+    fn fits_length_keeping_char_classes() {
+        let cases = [
+            ("abcdefgh", None, "abcdefgh"),
+            ("abcdefgh", Some(0), "abcdefgh"),
+            ("abcdefgh", Some(8), "abcdefgh"),
+            ("abcdefgh", Some(20), "abcdefgh"),
+            ("abcdefgh", Some(4), "abcd"),
+            ("abcdE1!x", Some(4), "dE1!"),
+            ("abcdE1!x", Some(3), "abc"),
+            ("aB3$xyzw", Some(4), "aB3$"),
+        ];
+        for (password, length, fitted) in cases {
+            assert_eq!(fit_length(password, length), fitted, "{:?} to {:?}", password, length);
+        }
+    }
+
+    #[test]
+    fn wordlist_is_eff_large() {
+        let words: Vec<&str> = WORDLIST.lines().collect();
+        assert_eq!(words.len(), 7776);
+        assert_eq!(words.first(), Some(&"abacus"));
+        assert_eq!(words.last(), Some(&"zoom"));
+    }
+
+    #[test]
+    fn maps_password_onto_words() {
+        // Passphrases must never change for the same derived password
+        let password = "Qk3#vT9!mZp2@Lx7";
+        assert_eq!(to_passphrase(password, &passphrase(4, "-")), "lumber-fraction-tripod-tacky");
+        assert_eq!(to_passphrase(password, &passphrase(0, "-")), "");
+        // More words only extend the passphrase
+        assert_eq!(to_passphrase(password, &passphrase(6, " ")), "lumber fraction tripod tacky embellish eccentric");
+    }
+
+    #[test]
+    fn limits_words_to_password_entropy() {
+        assert_eq!(max_words(0), 0);
+        assert_eq!(max_words(1), 0);
+        assert_eq!(max_words(2), 1);
+        assert_eq!(max_words(16), 8);
+        let password = "Qk3#vT9!mZp2@Lx7";
+        let words = to_passphrase(password, &passphrase(12, " "));
+        // Words past 8 would only be padding from the start of wordlist
+        assert_eq!(words, to_passphrase(password, &passphrase(8, " ")));
+        assert_eq!(words.split(' ').next_back(), Some("saxophone"));
+        assert!(!words.contains("abacus"));
+    }
+
+    #[test]
+    fn shapes_password() {
+        let password = "abcdE1!x";
+        assert_eq!(shape_password(password, Some(4), None), "dE1!");
+        assert_eq!(
+            shape_password(password, Some(4), Some(&passphrase(3, "."))),
+            to_passphrase(password, &passphrase(3, ".")),
+        );
+    }
+
+    #[test]
+    fn names_generation_aliases() {
+        assert_eq!(generation_alias("github.com", 0), "github.com");
+        assert_eq!(generation_alias("github.com", 1), "github.com#2");
+        assert_eq!(generation_alias("github.com", 9), "github.com#10");
+    }
+
+    #[test]
+    fn turns_password_into_username() {
+        assert_eq!(username_alias("github.com"), "github.com@username");
+        assert_eq!(to_username("42Ab-cD_efGh1234567"), "abcdefgh1234");
+        assert_eq!(to_username("1234"), "");
+    }
+}
@@ -13,6 +13,15 @@ const wasm_build_cmd = 'wasm-pack build --release \
     --features=keyboard \
     --out-dir=';
 
+const worker_dst_dir = 'www/';
+const worker_tmp_dir = 'www/worker/';
+const worker_build_cmd = 'wasm-pack build --release \
+    --target=no-modules \
+    --no-typescript \
+    --features=worker \
+    --out-name=worker \
+    --out-dir=';
+
 module.exports = function (context) {
     // Copy CSS file
     console.log('Copying `' + css_src + '` to `' + css_dst_dir + css_src);
@@ -23,4 +32,13 @@ module.exports = function (context) {
     execSync(wasm_build_cmd + cordova_root + wasm_dst_dir, {'cwd': '../'});
     console.log('Removing excess files...');
     rmSync(wasm_dst_dir + 'package.json');
+
+    // Build WASM worker files
+    console.log('Building WASM worker module...');
+    execSync(worker_build_cmd + cordova_root + worker_tmp_dir, {'cwd': '../'});
+    for (const file of ['worker.js', 'worker_bg.wasm']) {
+        copyFileSync(worker_tmp_dir + file, worker_dst_dir + file);
+    }
+    console.log('Removing excess files...');
+    rmSync(worker_tmp_dir, { recursive: true });
 }
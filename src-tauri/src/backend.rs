@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use tokio::sync::oneshot;
+
+use psh::{Psh, PshStore, ZeroizingString};
+use psh_gui_common::alias_meta::MetaStore;
+use psh_gui_common::aliases::{self, aliases, aliases_with_passwords, copy_aliases, password_changes};
+use psh_gui_common::{
+    charset_by_id, check_records, import_preview, psh_secret, AliasInfo, BackupFile, ImportPreview, Passphrase,
+    PasswordChange, Secret, UnlockError, BACKUP_VERSION, DB_VERSION,
+};
+
+use crate::store::{FileStore, MemoryStore};
+
+type Job = Box<dyn FnOnce(&mut PshState) + Send>;
+
+// Creates a copy of Psh database encrypted with another master password
+fn rekey(psh: &Psh, new_password: ZeroizingString) -> Result<(Psh, MemoryStore), String> {
+    let db = MemoryStore::default();
+    let mut new_psh = Psh::new(new_password, db.clone())
+        .map_err(|e| e.to_string())?;
+    copy_aliases(psh, &mut new_psh).map_err(|e| e.to_string())?;
+    Ok((new_psh, db))
+}
+
+pub struct PshState {
+    pub psh: Option<Psh>,
+    pub meta: MetaStore,
+    pub db_path: PathBuf,
+    // New master password along with database and alias settings re-encrypted with it
+    // (until change is confirmed)
+    pub rekeyed: Option<(ZeroizingString, MemoryStore, String)>,
+    // Stored passwords of aliases read from backup file (until import is applied)
+    pub imported_passwords: HashMap<String, Secret>,
+}
+
+impl PshState {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self {
+            psh: None,
+            meta: MetaStore::default(),
+            db_path,
+            rekeyed: None,
+            imported_passwords: HashMap::new(),
+        }
+    }
+
+    pub fn psh(&mut self) -> Result<&mut Psh, String> {
+        self.psh.as_mut().ok_or_else(|| "Psh is locked".to_string())
+    }
+
+    // Psh along with alias settings
+    fn unlocked(&mut self) -> Result<(&mut Psh, &mut MetaStore), String> {
+        let psh = self.psh.as_mut().ok_or_else(|| "Psh is locked".to_string())?;
+        Ok((psh, &mut self.meta))
+    }
+
+    pub fn aliases(&mut self) -> Result<Vec<AliasInfo>, String> {
+        let (psh, meta) = self.unlocked()?;
+        Ok(aliases(psh, meta))
+    }
+
+    /// Stores alias in psh database along with settings Psh has no place for.
+    pub fn append_alias(&mut self, info: AliasInfo) -> Result<(), String> {
+        let (psh, meta) = self.unlocked()?;
+        aliases::append_alias(psh, meta, info).map_err(|e| e.to_string())?;
+        self.save_meta()
+    }
+
+    /// Returns password of alias: either stored one or derived one.
+    pub fn password(
+        &mut self,
+        alias: &str,
+        secret: Option<Secret>,
+        charset: usize,
+        length: Option<usize>,
+        generation: u32,
+        passphrase: Option<&Passphrase>,
+    ) -> Result<Secret, String> {
+        let charset = charset_by_id(charset).map_err(|e| e.to_string())?;
+        let (psh, meta) = self.unlocked()?;
+        Ok(aliases::password(psh, meta, alias, secret, charset, length, generation, passphrase))
+    }
+
+    /// Stores password of alias adding the alias if it doesn't exist.
+    pub fn store_password(&mut self, alias: String, password: Secret) -> Result<(), String> {
+        let (psh, meta) = self.unlocked()?;
+        aliases::store_password(psh, meta, alias, password).map_err(|e| e.to_string())?;
+        self.save_meta()
+    }
+
+    /// Derives login for alias, so that accounts on different sites can't be linked by username.
+    pub fn derive_username(&mut self, alias: &str) -> Result<String, String> {
+        Ok(aliases::username(self.psh()?, alias))
+    }
+
+    pub fn remove_alias(&mut self, alias: String) -> Result<(), String> {
+        let (psh, meta) = self.unlocked()?;
+        aliases::remove_alias(psh, meta, alias).map_err(|e| e.to_string())?;
+        self.save_meta()
+    }
+
+    /// Replaces alias with another one (possibly with the same name but different settings).
+    pub fn update_alias(&mut self, alias: String, new: AliasInfo) -> Result<(), String> {
+        let (psh, meta) = self.unlocked()?;
+        aliases::update_alias(psh, meta, alias, new).map_err(|e| e.to_string())?;
+        self.save_meta()
+    }
+
+    // Seals alias settings with master password and saves them
+    fn save_meta(&mut self) -> Result<(), String> {
+        let (psh, meta) = self.unlocked()?;
+        let record = meta.seal(psh).map_err(|e| e.to_string())?;
+        self.db().save_meta(&record).map_err(|e| e.to_string())
+    }
+
+    pub fn db(&self) -> FileStore {
+        FileStore::new(self.db_path.clone())
+    }
+
+    /// Unlocks Psh telling wrong master password apart from database failures.
+    // Opens psh database, so that Psh gets its records or error is reported
+    // (instead of Psh seeing unreadable database as an empty one)
+    fn open_db(&self) -> Result<FileStore, String> {
+        FileStore::open(self.db_path.clone()).map_err(|e| e.to_string())
+    }
+
+    pub fn unlock(&mut self, master_password: Secret) -> Result<Vec<AliasInfo>, UnlockError> {
+        let mut db = FileStore::open(self.db_path.clone()).map_err(|e| {
+            match e.downcast_ref::<io::Error>().map(io::Error::kind) {
+                // Database file isn't text
+                Some(io::ErrorKind::InvalidData) => UnlockError::CorruptDb(e.to_string()),
+                _ => UnlockError::StorageFailure(e.to_string()),
+            }
+        })?;
+        let records = db.contents();
+        let version = db.version().map_err(|e| UnlockError::StorageFailure(e.to_string()))?;
+        if version > DB_VERSION {
+            return Err(UnlockError::UnsupportedDbVersion(version));
+        }
+        check_records(&records)?;
+        let meta_record = db.load_meta().map_err(|e| UnlockError::StorageFailure(e.to_string()))?;
+        // Records are well-formed, so Psh can only fail to decrypt them
+        let psh = Psh::new(psh_secret(master_password), db).map_err(|e| {
+            if records.is_empty() { UnlockError::Other(e.to_string()) } else { UnlockError::WrongPassword }
+        })?;
+        let meta = MetaStore::load(&psh, meta_record.as_deref())
+            .map_err(|e| UnlockError::CorruptDb(e.to_string()))?;
+        self.psh = Some(psh);
+        self.meta = meta;
+        Ok(self.aliases().unwrap())
+    }
+
+    pub fn lock(&mut self) {
+        self.psh = None;
+        self.meta = MetaStore::default();
+        self.rekeyed = None;
+        self.imported_passwords.clear();
+    }
+
+    /// Locks Psh and deletes its database.
+    pub fn wipe_db(&mut self) -> Result<(), String> {
+        self.lock();
+        self.db().wipe().map_err(|e| e.to_string())?;
+        if self.db().exists() {
+            return Err("Database still exists after wipe".to_string());
+        }
+        Ok(())
+    }
+
+    /// Re-encrypts Psh database with new master password and returns passwords that
+    /// change along with it. Database isn't saved until the change is confirmed.
+    /// Returns `None` if old master password is wrong.
+    pub fn preview_master_password_change(
+        &mut self,
+        old_password: Secret,
+        new_password: Secret,
+    ) -> Result<Option<Vec<PasswordChange>>, String> {
+        if Psh::new(psh_secret(old_password), self.open_db()?).is_err() {
+            return Ok(None);
+        }
+        let (psh, meta) = self.unlocked()?;
+        let new_password = psh_secret(new_password);
+        let (new_psh, new_db) = rekey(psh, new_password.clone())?;
+        let changes = password_changes(psh, &new_psh, meta).map_err(|e| e.to_string())?;
+        let new_meta = meta.seal(&new_psh).map_err(|e| e.to_string())?;
+        self.rekeyed = Some((new_password, new_db, new_meta));
+        Ok(Some(changes))
+    }
+
+    /// Saves database re-encrypted on master password change preview.
+    pub fn confirm_master_password_change(&mut self) -> Result<(), String> {
+        let (new_password, new_db, new_meta) = self.rekeyed.take()
+            .ok_or_else(|| "No master password change to confirm".to_string())?;
+        self.db().replace(new_db.contents(), new_meta).map_err(|e| e.to_string())?;
+        let psh = Psh::new(new_password, self.open_db()?).map_err(|e| e.to_string())?;
+        self.psh = Some(psh);
+        Ok(())
+    }
+
+    /// Returns backup file contents. Records and alias settings are encrypted
+    /// with master password unless `passphrase` is given.
+    pub fn export(&mut self, passphrase: Option<Secret>) -> Result<String, String> {
+        let (psh, meta) = self.unlocked()?;
+        let (records, meta) = match passphrase {
+            Some(passphrase) => {
+                let (new_psh, new_db) = rekey(psh, psh_secret(passphrase))?;
+                (new_db.contents(), meta.seal(&new_psh).map_err(|e| e.to_string())?)
+            }
+            None => {
+                let meta = meta.seal(psh).map_err(|e| e.to_string())?;
+                (self.open_db()?.contents(), meta)
+            }
+        };
+        let backup = BackupFile { version: BACKUP_VERSION, records, meta };
+        serde_json::to_string(&backup).map_err(|e| e.to_string())
+    }
+
+    /// Compares aliases from backup file with the ones stored in psh database.
+    pub fn read_import(&mut self, contents: String, passphrase: Secret) -> Result<ImportPreview, String> {
+        let backup: BackupFile = serde_json::from_str(&contents)
+            .map_err(|_| "Not a Psh backup file".to_string())?;
+        if backup.version > BACKUP_VERSION {
+            return Err("Unsupported backup file version".to_string());
+        }
+        let db = MemoryStore::new(backup.records);
+        let imported = Psh::new(psh_secret(passphrase), db)
+            .map_err(|_| "Wrong backup password".to_string())?;
+        let imported_meta = MetaStore::load(&imported, Some(&backup.meta))
+            .map_err(|_| "Alias settings in backup file are damaged".to_string())?;
+        let (psh, meta) = self.unlocked()?;
+        let current = aliases_with_passwords(psh, meta);
+        let mut preview = import_preview(&current, aliases_with_passwords(&imported, &imported_meta));
+        self.imported_passwords = preview.take_passwords();
+        Ok(preview)
+    }
+
+    /// Adds new aliases and replaces conflicting ones with imported ones.
+    pub fn apply_import(&mut self, new: Vec<AliasInfo>, replace: Vec<AliasInfo>) -> Result<(), String> {
+        let mut passwords = std::mem::take(&mut self.imported_passwords);
+        let (psh, meta) = self.unlocked()?;
+        aliases::apply_import(psh, meta, new, replace, &mut passwords).map_err(|e| e.to_string())?;
+        self.save_meta()
+    }
+}
+
+/// Owner of native Psh instance. Psh lives in a dedicated thread so that
+/// resource-intensive operations don't block Tauri main thread.
+pub struct Backend {
+    jobs: Sender<Job>,
+}
+
+impl Backend {
+    pub fn new(db_path: PathBuf) -> Self {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        thread::spawn(move || {
+            let mut state = PshState::new(db_path);
+            for job in queue {
+                job(&mut state);
+            }
+        });
+        Self { jobs }
+    }
+
+    pub async fn run<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce(&mut PshState) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        self.jobs
+            .send(Box::new(move |state| { tx.send(f(state)).ok(); }))
+            .expect("Psh thread is gone");
+        rx.await.expect("Psh thread is gone")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+
+    #[test]
+    fn wiped_db_does_not_exist() {
+        let dir = env::temp_dir().join(format!("psh-gui-test-{}", process::id()));
+        let mut state = PshState::new(dir.join("psh.db"));
+        state.db().import(vec!["YWJj".to_string()], Some("meta".to_string())).unwrap();
+        assert!(state.db().exists());
+        state.wipe_db().unwrap();
+        assert!(!state.db().exists());
+        assert_eq!(state.db().load_meta().unwrap(), None);
+        // Wiping missing database is fine
+        state.wipe_db().unwrap();
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn unreadable_db_is_not_empty() {
+        let dir = env::temp_dir().join(format!("psh-gui-test-unreadable-{}", process::id()));
+        let mut state = PshState::new(dir.join("psh.db"));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&state.db_path, [0xff, 0xfe, b'\n']).unwrap();
+        let res = state.unlock(Secret::new("password".to_string()));
+        assert!(matches!(res, Err(UnlockError::CorruptDb(_))));
+        assert!(state.psh.is_none());
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -3,8 +3,313 @@
   windows_subsystem = "windows"
 )]
 
+use std::fs;
+use std::path::PathBuf;
+
+use tauri::api::dialog::blocking::FileDialogBuilder;
+use tauri::{AppHandle, Manager, State, WindowEvent};
+
+use psh::PshStore;
+
+mod backend;
+mod store;
+
+use psh_gui_common::{AliasInfo, ImportPreview, Passphrase, PasswordChange, Secret, UnlockError};
+
+use backend::Backend;
+
+const DB_FILE_NAME: &str = "psh.db";
+// Config files the frontend may read and write (without extension)
+const CONFIG_FILES: [&str; 4] = ["settings", "lockout", "alias-names", "fingerprint-key"];
+// Event emitted to the frontend when app window gets minimized
+const WINDOW_MINIMIZED_EVENT: &str = "window-minimized";
+// Suggested name of exported database file
+const BACKUP_FILE_NAME: &str = "psh-backup.json";
+
+#[tauri::command]
+async fn db_exists(backend: State<'_, Backend>) -> Result<bool, String> {
+  Ok(backend.run(|state| state.db().exists()).await)
+}
+
+#[tauri::command]
+async fn import_webdb(
+  backend: State<'_, Backend>,
+  records: Vec<String>,
+  meta: Option<String>,
+) -> Result<(), String> {
+  backend.run(move |state| state.db().import(records, meta))
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Unlock failures are returned as `Ok(Err(..))` so that the frontend can tell them apart.
+#[tauri::command]
+async fn unlock(
+  backend: State<'_, Backend>,
+  master_password: Secret,
+) -> Result<Result<Vec<AliasInfo>, UnlockError>, String> {
+  Ok(backend.run(move |state| state.unlock(master_password)).await)
+}
+
+#[tauri::command]
+async fn derive_password(
+  backend: State<'_, Backend>,
+  alias: String,
+  secret: Option<Secret>,
+  charset: usize,
+  length: Option<usize>,
+  generation: u32,
+  passphrase: Option<Passphrase>,
+) -> Result<Secret, String> {
+  backend.run(move |state| {
+    state.password(&alias, secret, charset, length, generation, passphrase.as_ref())
+  }).await
+}
+
+#[tauri::command]
+async fn derive_username(backend: State<'_, Backend>, alias: String) -> Result<String, String> {
+  backend.run(move |state| state.derive_username(&alias)).await
+}
+
+#[tauri::command]
+async fn aliases(backend: State<'_, Backend>) -> Result<Vec<AliasInfo>, String> {
+  backend.run(|state| state.aliases()).await
+}
+
+#[tauri::command]
+async fn append_alias(
+  backend: State<'_, Backend>,
+  alias: String,
+  use_secret: bool,
+  charset: usize,
+  length: Option<usize>,
+  passphrase: Option<Passphrase>,
+) -> Result<Vec<AliasInfo>, String> {
+  backend.run(move |state| {
+    state.append_alias(AliasInfo { alias, use_secret, charset, length, passphrase, ..Default::default() })?;
+    state.aliases()
+  }).await
+}
+
+#[tauri::command]
+async fn store_password(
+  backend: State<'_, Backend>,
+  alias: String,
+  password: Secret,
+) -> Result<Vec<AliasInfo>, String> {
+  backend.run(move |state| {
+    state.store_password(alias, password)?;
+    state.aliases()
+  }).await
+}
+
+#[tauri::command]
+async fn remove_alias(
+  backend: State<'_, Backend>,
+  alias: String,
+) -> Result<Vec<AliasInfo>, String> {
+  backend.run(move |state| {
+    state.remove_alias(alias)?;
+    state.aliases()
+  }).await
+}
+
+#[tauri::command]
+async fn remove_aliases(
+  backend: State<'_, Backend>,
+  aliases: Vec<String>,
+) -> Result<Vec<AliasInfo>, String> {
+  backend.run(move |state| {
+    for alias in aliases {
+      state.remove_alias(alias)?;
+    }
+    state.aliases()
+  }).await
+}
+
+#[tauri::command]
+async fn update_alias(
+  backend: State<'_, Backend>,
+  alias: String,
+  mut new: AliasInfo,
+) -> Result<Vec<AliasInfo>, String> {
+  // Passwords are only stored with `store_password`
+  new.password = None;
+  backend.run(move |state| {
+    state.update_alias(alias, new)?;
+    state.aliases()
+  }).await
+}
+
+#[tauri::command]
+async fn preview_master_password_change(
+  backend: State<'_, Backend>,
+  old_password: Secret,
+  new_password: Secret,
+) -> Result<Option<Vec<PasswordChange>>, String> {
+  backend.run(move |state| state.preview_master_password_change(old_password, new_password)).await
+}
+
+#[tauri::command]
+async fn confirm_master_password_change(backend: State<'_, Backend>) -> Result<(), String> {
+  backend.run(|state| state.confirm_master_password_change()).await
+}
+
+#[tauri::command]
+async fn cancel_master_password_change(backend: State<'_, Backend>) -> Result<(), String> {
+  backend.run(|state| state.rekeyed = None).await;
+  Ok(())
+}
+
+/// Returns `false` if user cancelled export.
+#[tauri::command]
+async fn export_db(
+  backend: State<'_, Backend>,
+  passphrase: Option<Secret>,
+) -> Result<bool, String> {
+  let path = FileDialogBuilder::new()
+    .set_file_name(BACKUP_FILE_NAME)
+    .add_filter("Psh backup", &["json"])
+    .save_file();
+  let Some(path) = path else {
+    return Ok(false);
+  };
+  let contents = backend.run(move |state| state.export(passphrase)).await?;
+  fs::write(path, contents).map_err(|e| e.to_string())?;
+  Ok(true)
+}
+
+/// Returns `false` if user cancelled saving.
+#[tauri::command]
+async fn save_text_file(file_name: String, contents: String) -> Result<bool, String> {
+  let path = FileDialogBuilder::new()
+    .set_file_name(&file_name)
+    .add_filter("Text", &["txt"])
+    .save_file();
+  let Some(path) = path else {
+    return Ok(false);
+  };
+  fs::write(path, contents).map_err(|e| e.to_string())?;
+  Ok(true)
+}
+
+/// Returns `None` if user cancelled import.
+#[tauri::command]
+async fn read_import(
+  backend: State<'_, Backend>,
+  passphrase: Secret,
+) -> Result<Option<ImportPreview>, String> {
+  let path = FileDialogBuilder::new()
+    .add_filter("Psh backup", &["json"])
+    .pick_file();
+  let Some(path) = path else {
+    return Ok(None);
+  };
+  let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+  backend.run(move |state| state.read_import(contents, passphrase))
+    .await
+    .map(Some)
+}
+
+#[tauri::command]
+async fn apply_import(
+  backend: State<'_, Backend>,
+  new: Vec<AliasInfo>,
+  replace: Vec<AliasInfo>,
+) -> Result<Vec<AliasInfo>, String> {
+  backend.run(move |state| {
+    state.apply_import(new, replace)?;
+    state.aliases()
+  }).await
+}
+
+fn config_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+  if !CONFIG_FILES.contains(&name) {
+    return Err(format!("Unknown config file: {}", name));
+  }
+  app.path_resolver()
+    .app_config_dir()
+    .map(|dir| dir.join(name).with_extension("json"))
+    .ok_or_else(|| "Failed to resolve app config directory".to_string())
+}
+
+/// Returns `None` if config file was never written.
+#[tauri::command]
+async fn read_config(app: AppHandle, name: String) -> Result<Option<String>, String> {
+  let path = config_path(&app, &name)?;
+  if !path.exists() {
+    return Ok(None);
+  }
+  fs::read_to_string(path).map(Some).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn write_config(app: AppHandle, name: String, contents: String) -> Result<(), String> {
+  let path = config_path(&app, &name)?;
+  let write = || -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    // Write via temporary file so that config never gets half-written
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, contents)?;
+    fs::rename(tmp_path, &path)
+  };
+  write().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn wipe_db(backend: State<'_, Backend>) -> Result<(), String> {
+  backend.run(|state| state.wipe_db()).await
+}
+
+#[tauri::command]
+async fn lock(backend: State<'_, Backend>) -> Result<(), String> {
+  backend.run(|state| state.lock()).await;
+  Ok(())
+}
+
 fn main() {
   tauri::Builder::default()
+    .setup(|app| {
+      let data_dir = app.path_resolver()
+        .app_data_dir()
+        .expect("failed to resolve app data directory");
+      app.manage(Backend::new(data_dir.join(DB_FILE_NAME)));
+      Ok(())
+    })
+    .on_window_event(|event| {
+      if let WindowEvent::Resized(_) = event.event() {
+        let window = event.window();
+        if window.is_minimized().unwrap_or(false) {
+          window.emit(WINDOW_MINIMIZED_EVENT, ()).ok();
+        }
+      }
+    })
+    .invoke_handler(tauri::generate_handler![
+      db_exists,
+      import_webdb,
+      unlock,
+      derive_password,
+      derive_username,
+      aliases,
+      append_alias,
+      store_password,
+      remove_alias,
+      remove_aliases,
+      update_alias,
+      preview_master_password_change,
+      confirm_master_password_change,
+      cancel_master_password_change,
+      export_db,
+      read_import,
+      apply_import,
+      save_text_file,
+      read_config,
+      write_config,
+      wipe_db,
+      lock,
+    ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
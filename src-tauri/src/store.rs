@@ -0,0 +1,235 @@
+use std::cell::RefCell;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use anyhow::{bail, Result};
+use fs2::FileExt;
+
+use psh::{PshStore, ZeroizingString};
+use psh_gui_common::DB_VERSION;
+
+/// Psh database stored in a file, one record per line. Sealed alias settings
+/// are kept in a companion file.
+///
+/// Every change rewrites the whole file atomically (via temporary file and rename)
+/// while holding an exclusive lock on a companion lock file, so neither a crash
+/// nor another app instance can leave the database half-written.
+///
+/// Psh reads records through an infallible iterator, so they are read beforehand
+/// by [`FileStore::open`] and handed out from memory.
+pub struct FileStore {
+    path: PathBuf,
+    // Database records as last read or written
+    records: Vec<String>,
+}
+
+impl FileStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, records: Vec::new() }
+    }
+
+    /// Opens database reading all its records. Fails if records can't be read
+    /// or database directory isn't writable.
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let mut store = Self::new(path);
+        store.records = store.locked(|store| store.read_records())?;
+        Ok(store)
+    }
+
+    /// Replaces all database records along with alias settings sealed with them.
+    pub fn replace(&mut self, records: Vec<String>, meta: String) -> Result<()> {
+        self.locked(|store| {
+            store.write_records(&records)?;
+            store.write_meta(&meta)
+        })
+    }
+
+    /// Creates database from records of another store (e.g. Psh web database).
+    /// Fails if database already exists.
+    pub fn import(&mut self, records: Vec<String>, meta: Option<String>) -> Result<()> {
+        self.locked(|store| {
+            if store.exists() {
+                bail!("Database already exists");
+            }
+            store.write_records(&records)?;
+            match meta {
+                Some(meta) => store.write_meta(&meta),
+                None => Ok(()),
+            }
+        })
+    }
+
+    /// Reads sealed alias settings (if any).
+    pub fn load_meta(&mut self) -> Result<Option<String>> {
+        self.locked(|store| {
+            let path = store.meta_path();
+            if !path.exists() {
+                return Ok(None);
+            }
+            Ok(Some(fs::read_to_string(path)?))
+        })
+    }
+
+    /// Replaces sealed alias settings.
+    pub fn save_meta(&mut self, meta: &str) -> Result<()> {
+        self.locked(|store| store.write_meta(meta))
+    }
+
+    /// Returns database records read on opening (or written since).
+    pub fn contents(&self) -> Vec<String> {
+        self.records.clone()
+    }
+
+    /// Returns database version. Databases of earlier app versions have no version mark.
+    pub fn version(&self) -> Result<u32> {
+        let path = self.version_path();
+        if !path.exists() {
+            return Ok(DB_VERSION);
+        }
+        Ok(fs::read_to_string(path)?.trim().parse()?)
+    }
+
+    /// Deletes database file.
+    pub fn wipe(&mut self) -> Result<()> {
+        self.locked(|store| {
+            for path in [&store.path, &store.meta_path(), &store.version_path()] {
+                if path.exists() {
+                    fs::remove_file(path)?;
+                }
+            }
+            store.records.clear();
+            Ok(())
+        })
+    }
+
+    fn lock_path(&self) -> PathBuf {
+        self.path.with_extension("lock")
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        self.path.with_extension("tmp")
+    }
+
+    fn version_path(&self) -> PathBuf {
+        self.path.with_extension("version")
+    }
+
+    fn meta_path(&self) -> PathBuf {
+        self.path.with_extension("meta")
+    }
+
+    // Runs `f` while holding exclusive lock on database
+    fn locked<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.lock_path())?;
+        lock.lock_exclusive()?;
+        let res = f(self);
+        lock.unlock()?;
+        res
+    }
+
+    fn read_records(&self) -> Result<Vec<String>> {
+        if !self.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&self.path)?;
+        Ok(contents.lines().map(|line| line.to_string()).collect())
+    }
+
+    fn write_records(&mut self, records: &[String]) -> Result<()> {
+        let tmp_path = self.tmp_path();
+        let mut file = File::create(&tmp_path)?;
+        for record in records {
+            writeln!(file, "{}", record)?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+        fs::write(self.version_path(), DB_VERSION.to_string())?;
+        self.records = records.to_vec();
+        Ok(())
+    }
+
+    fn write_meta(&self, meta: &str) -> Result<()> {
+        let tmp_path = self.tmp_path();
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(meta.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, self.meta_path())?;
+        Ok(())
+    }
+}
+
+impl PshStore for FileStore {
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    fn records(&self) -> Box<dyn Iterator<Item = ZeroizingString>> {
+        let records = self.records.clone();
+        Box::new(records.into_iter().map(ZeroizingString::new))
+    }
+
+    fn append(&mut self, record: &ZeroizingString) -> Result<()> {
+        let record = record.to_string();
+        self.locked(|store| {
+            let mut records = store.read_records()?;
+            records.push(record);
+            store.write_records(&records)
+        })
+    }
+
+    fn delete(&mut self, record: &ZeroizingString) -> Result<()> {
+        let record = record.to_string();
+        self.locked(|store| {
+            let mut records = store.read_records()?;
+            records.retain(|r| *r != record);
+            store.write_records(&records)
+        })
+    }
+}
+
+/// Psh database held in memory. Used as a staging area for database re-encryption.
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    records: Rc<RefCell<Vec<String>>>,
+}
+
+impl MemoryStore {
+    pub fn new(records: Vec<String>) -> Self {
+        Self { records: Rc::new(RefCell::new(records)) }
+    }
+
+    pub fn contents(&self) -> Vec<String> {
+        self.records.borrow().clone()
+    }
+}
+
+impl PshStore for MemoryStore {
+    fn exists(&self) -> bool {
+        !self.records.borrow().is_empty()
+    }
+
+    fn records(&self) -> Box<dyn Iterator<Item = ZeroizingString>> {
+        let records = self.records.borrow().clone();
+        Box::new(records.into_iter().map(ZeroizingString::new))
+    }
+
+    fn append(&mut self, record: &ZeroizingString) -> Result<()> {
+        self.records.borrow_mut().push(record.to_string());
+        Ok(())
+    }
+
+    fn delete(&mut self, record: &ZeroizingString) -> Result<()> {
+        let record = record.to_string();
+        self.records.borrow_mut().retain(|r| *r != record);
+        Ok(())
+    }
+}
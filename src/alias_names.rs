@@ -0,0 +1,18 @@
+use crate::settings::{read_config, write_config};
+
+// Name of alias names config
+const CONFIG_NAME: &str = "alias-names";
+
+/// Loads unencrypted list of alias names from config file (Tauri app) or Web Storage.
+/// The list lets user know which passwords to change after master password is lost.
+pub async fn load() -> Vec<String> {
+    read_config(CONFIG_NAME).await
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Saves unencrypted list of alias names to config file (Tauri app) or Web Storage.
+pub async fn save(names: &[String]) -> Result<(), String> {
+    let json = serde_json::to_string(names).map_err(|e| e.to_string())?;
+    write_config(CONFIG_NAME, json).await
+}
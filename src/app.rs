@@ -1,25 +1,45 @@
-use std::cell::OnceCell;
 use std::time::Duration;
 
 use gloo_events::EventListener;
-use gloo_timers::callback::Timeout;
+use gloo_timers::callback::{Interval, Timeout};
 use wasm_bindgen::prelude::*;
 use yew::prelude::*;
 use yew::platform::{spawn_local, time::sleep};
 
-use psh::{CharSet, Psh, PshStore, ZeroizingString};
-use psh_webdb::PshWebDb;
+use psh::CharSet;
+
+use crate::alias_names;
+use crate::clipboard;
+use crate::fingerprint;
+use crate::backend::{
+    charset_by_id, charset_id, is_tauri, save_text_file, AliasInfo, Backend, ImportPreview, Passphrase,
+    PasswordChange, PshRequest, PshResponse, UnlockError, Username,
+};
+use crate::backend::tauri::on_window_minimized;
+use crate::lockout::{self, Lockout};
+use crate::secret::{mask, SecretEdit, SecretString};
+use crate::settings::{self, Settings};
+use crate::site_rules::{self, SiteRule};
+use crate::strength::{self, Strength, SCORE_LABELS};
 
 mod components;
 
 use components::alias_input::AliasInput;
+use components::alias_manager::{
+    length_label, with_word_count, word_count_label, AliasManager, LENGTHS, SEPARATORS, WORD_COUNTS,
+};
+use components::db_reset::DbReset;
+use components::fingerprint::Fingerprint;
+use components::import_review::ImportReview;
+use components::password_changes::PasswordChanges;
+use components::password_store::PasswordStore;
 use components::secret_input::SecretInput;
+use components::settings_editor::SettingsEditor;
 use components::triswitch::Triswitch;
 use components::collapsible::Collapsible;
 #[cfg(feature = "keyboard")]
 use components::keyboard::Keyboard;
 
-const LOCK_TIMEOUT_SEC: u32 = 20;
 
 #[wasm_bindgen]
 extern "C" {
@@ -32,6 +52,12 @@ enum AppState {
     New,
     Unlocking,
     Initialized,
+    ChangingMasterPassword,
+    ManagingAliases,
+    StoringPassword,
+    Backup,
+    Settings,
+    ResettingDb,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -45,6 +71,34 @@ enum AliasHandle {
 struct AppEventListeners {
     _pause: EventListener,
     _resume: EventListener,
+    _keydown: EventListener,
+    _pointerdown: EventListener,
+}
+
+// Describes unlock failure along with the way to recover from it
+fn unlock_error_message(e: &UnlockError) -> String {
+    match e {
+        UnlockError::WrongPassword => "Wrong master password".to_string(),
+        UnlockError::CorruptDb(e) => format!(
+            "Psh database is corrupted ({}). Restore it from backup or reset it", e
+        ),
+        UnlockError::StorageFailure(e) => format!(
+            "Can't access storage: {}. Free some space or check permissions and retry", e
+        ),
+        UnlockError::UnsupportedDbVersion(version) => format!(
+            "Psh database was created by a newer app version (database version {}). \
+            Update the app to unlock it", version
+        ),
+        UnlockError::Other(e) => format!("Failed to unlock Psh: {}", e),
+    }
+}
+
+// Suggested name of file with saved alias names
+const ALIAS_NAMES_FILE_NAME: &str = "psh-aliases.txt";
+
+// Ctrl+Shift+L locks the app
+fn is_lock_shortcut(e: &KeyboardEvent) -> bool {
+    e.ctrl_key() && e.shift_key() && e.key().eq_ignore_ascii_case("l")
 }
 
 fn alias_handle_id(handle: AliasHandle) -> usize {
@@ -55,11 +109,13 @@ fn alias_handle_id(handle: AliasHandle) -> usize {
     }
 }
 
-fn charset_id(charset: CharSet) -> usize {
-    match charset {
-        CharSet::Standard => 0,
-        CharSet::RequireAll => 1,
-        CharSet::Reduced => 2,
+fn alias_handle_by_id(id: usize) -> AliasHandle {
+    match id {
+        0 => AliasHandle::Store,
+        1 => AliasHandle::Ignore,
+        2 => AliasHandle::Remove,
+        // Unknown ids can only come from damaged settings
+        _ => AliasHandle::Store,
     }
 }
 
@@ -73,19 +129,77 @@ fn is_android() -> bool {
 pub enum Msg {
     OnFocusOut(FocusEvent),
     OnInputFocus(NodeRef),
-    OnPasswordInput(String),
-    OnPassword2Input(String),
+    OnPasswordInput(SecretEdit),
+    OnPassword2Input(SecretEdit),
+    OverrideMpPolicy,
+    CancelMpPolicyOverride,
     OnAliasInput((String, bool)),
-    OnSecretInput(String),
+    OnSecretInput(SecretEdit),
     OnEnterPressed,
     Login,
+    CancelUnlock,
+    OnUnlockTick,
+    OnLockoutLoaded(Lockout),
+    OnFingerprintKeyLoaded(Option<u64>),
+    OnLockoutTick,
+    RetryDbCheck,
+    // Shows database reset (and whether backup should be restored afterwards)
+    ShowDbReset(bool),
+    CloseDbReset,
+    OnAliasNamesLoaded(Vec<String>),
+    SaveAliasNames,
+    OnAliasNamesSaved(Result<bool, String>),
+    ResetDb,
     Process,
-    Initialize(Option<Psh>),
+    OnBackendResponse(PshResponse),
     SetCharset(String),
+    SetLength(String),
+    SetWordCount(String),
+    SetSeparator(String),
+    SetGeneration(String),
+    // Switch known alias to the next password generation
+    RotateAlias,
+    // Switch known alias back to the previous password generation
+    RollBackAlias,
+    CopyUsername,
     SetAliasHandle(String),
     OnOptionsCollapsibleClick(bool),
+    RevealPassword(bool),
+    OnPasswordTick,
+    CopyPassword,
+    OnPasswordCopied(SecretString),
+    OnClipboardTick,
+    ClearClipboard,
+    ShowAliasManager,
+    CloseAliasManager,
+    ShowPasswordStore,
+    StorePassword((String, SecretString)),
+    ClosePasswordStore,
+    UpdateAlias((String, AliasInfo)),
+    RemoveAliases(Vec<String>),
+    ShowMasterPasswordChange,
+    CloseMasterPasswordChange,
+    OnOldPasswordInput(SecretEdit),
+    OnNewPasswordInput(SecretEdit),
+    OnNewPassword2Input(SecretEdit),
+    PreviewMasterPasswordChange,
+    ConfirmMasterPasswordChange,
+    OnSettingsLoaded(Settings),
+    ShowSettings,
+    CloseSettings,
+    SaveSettings(Settings),
+    ShowBackup,
+    CloseBackup,
+    OnBackupPasswordInput(SecretEdit),
+    OnBackupPassword2Input(SecretEdit),
+    Export,
+    Import,
+    ApplyImport((Vec<AliasInfo>, Vec<AliasInfo>)),
+    CancelImport,
     OnAppPause,
     OnAppResume,
+    OnWindowMinimized,
+    OnUserActivity,
     Lock,
     #[cfg(feature = "keyboard")]
     OnKbInput(String),
@@ -96,18 +210,74 @@ pub enum Msg {
 pub struct App {
     // App state
     state: AppState,
-    // Psh instance
-    psh: OnceCell<Psh>,
-    // App lock timeout
+    // Psh backend
+    backend: Backend,
+    // Whether psh database exists
+    db_exists: bool,
+    // Unlock progress timer
+    unlock_timer: Option<Interval>,
+    // Seconds elapsed since unlock start
+    unlock_elapsed: u32,
+    // Failed unlock attempts
+    lockout: Lockout,
+    // Seconds left before next unlock attempt is allowed
+    lockout_countdown: u32,
+    // Unlock back-off timer
+    lockout_timer: Option<Interval>,
+    // Whether psh database was wiped after too many failed unlock attempts
+    db_wiped: bool,
+    // Reason of last unlock failure
+    unlock_error: Option<UnlockError>,
+    // Whether user confirmed psh database reset (and awaits it)
+    db_reset_requested: bool,
+    // Whether backup should be imported once psh gets unlocked after database reset
+    restore_backup: bool,
+    // Unencrypted alias names that can be saved before database reset
+    recovery_alias_names: Vec<String>,
+    // Outcome of alias names saving
+    recovery_msg: Option<String>,
+    // User settings
+    settings: Settings,
+    // App lock timeout (after app goes to background)
     lock_timeout: Option<Timeout>,
+    // App lock timeout (after last user input)
+    idle_timeout: Option<Timeout>,
     // Master password
-    master_password: String,
+    master_password: SecretString,
     // Second master password value (from second input) on db initialization
-    master_password2: String,
+    master_password2: SecretString,
     // 'Master password is wrong' flag
     mp_wrong: bool,
+    // Key of master password fingerprint
+    fingerprint_key: Option<u64>,
+    // Strength of master password being set on db initialization
+    mp_strength: Option<Strength>,
+    // Whether user asked to use master password weaker than settings require
+    weak_mp_confirming: bool,
+    // Whether user confirmed use of weak master password
+    weak_mp_accepted: bool,
+    // Current master password (on master password change)
+    old_master_password: SecretString,
+    // New master password (on master password change)
+    new_master_password: SecretString,
+    // Second new master password value (on master password change)
+    new_master_password2: SecretString,
+    // Whether master password change is in progress
+    mp_change_in_progress: bool,
+    // Passwords that change along with master password (awaiting user confirmation)
+    password_changes: Option<Vec<PasswordChange>>,
+    // Password of backup file (master password is used for export if empty)
+    backup_password: SecretString,
+    // Second backup password value (on export)
+    backup_password2: SecretString,
+    // Whether export or import is in progress
+    backup_in_progress: bool,
+    // Outcome of last export or import
+    backup_msg: Option<String>,
+    // Aliases read from backup file that await user confirmation
+    import_preview: Option<ImportPreview>,
     // Aliases that are stored in psh database
-    known_aliases: Vec<String>,
+    known_aliases: Vec<AliasInfo>,
     // Currently input alias
     alias: String,
     // Currently selected alias handle
@@ -117,15 +287,41 @@ pub struct App {
     // Whether current alias should use secret or not
     use_secret: bool,
     // Currently input secret
-    secret: String,
+    secret: SecretString,
     // Currently selected charset
     charset: CharSet,
     // Last user choice of charset
     charset_user_choice: CharSet,
+    // Currently selected length of derived password (full length if not set)
+    length: Option<usize>,
+    // Last user choice of derived password length
+    length_user_choice: Option<usize>,
+    // Currently selected passphrase settings (password of characters is derived if not set)
+    passphrase: Option<Passphrase>,
+    // Last user choice of passphrase settings
+    passphrase_user_choice: Option<Passphrase>,
+    // Password rule of the site new alias stands for (charset and length are preset by it)
+    site_rule: Option<SiteRule>,
+    // Password generation of current alias to derive (older ones are for emergency logins)
+    generation: u32,
     // Whether current alias is stored in psh database or not
     known_alias: bool,
     // Derived password
-    password_msg: String,
+    password_msg: SecretString,
+    // Username of alias that password was derived for
+    username_msg: Option<String>,
+    // Whether derived password is shown in clear text
+    password_revealed: bool,
+    // Seconds left before derived password is wiped
+    password_countdown: u32,
+    // Derived password expiry timer
+    password_timer: Option<Interval>,
+    // Password that was copied to clipboard
+    clipboard_password: Option<SecretString>,
+    // Seconds left before clipboard is cleared
+    clipboard_countdown: u32,
+    // Clipboard clearing timer
+    clipboard_timer: Option<Interval>,
     // Password element NodeRef
     password_ref: NodeRef,
     // NodeRef of currently focused input
@@ -142,8 +338,93 @@ pub struct App {
 impl App {
     fn mp_looks_valid(&self) -> bool {
         self.master_password.len() >= 8
-            && if !PshWebDb::new().exists() { self.master_password == self.master_password2 }
-                    else { true }
+            && if !self.db_exists {
+                    self.master_password == self.master_password2
+                        && (self.mp_strong_enough() || self.weak_mp_accepted)
+                }
+                else { true }
+    }
+
+    fn mp_strong_enough(&self) -> bool {
+        self.mp_strength.as_ref()
+            .is_some_and(|strength| strength.score >= self.settings.min_mp_score)
+    }
+
+    // Re-estimates strength of master password being set on db initialization
+    fn update_mp_strength(&mut self) {
+        self.mp_strength = if !self.db_exists && !self.master_password.is_empty() {
+            Some(strength::estimate(&self.master_password))
+        } else {
+            None
+        };
+        self.weak_mp_confirming = false;
+        self.weak_mp_accepted = false;
+    }
+
+    fn can_login(&self) -> bool {
+        self.mp_looks_valid()
+            && self.lockout_countdown == 0
+            // There's no point to retry until broken database is dealt with
+            && !matches!(
+                self.unlock_error,
+                Some(UnlockError::CorruptDb(_) | UnlockError::UnsupportedDbVersion(_))
+            )
+    }
+
+    // Starts countdown of unlock back-off (if any)
+    fn start_lockout_countdown(&mut self, ctx: &Context<Self>) {
+        self.lockout_countdown = self.lockout.seconds_left();
+        self.lockout_timer = if self.lockout_countdown > 0 {
+            let scope = ctx.link().clone();
+            Some(Interval::new(1_000, move || scope.send_message(Msg::OnLockoutTick)))
+        } else {
+            None
+        };
+    }
+
+    fn save_lockout(&self) {
+        let lockout = self.lockout.clone();
+        spawn_local(async move {
+            if let Err(e) = lockout::save(&lockout).await {
+                log(&format!("Failed to save failed unlock attempts: {}", e));
+            }
+        });
+    }
+
+    fn new_mp_looks_valid(&self) -> bool {
+        !self.old_master_password.is_empty()
+            && self.new_master_password.len() >= 8
+            && self.new_master_password == self.new_master_password2
+            && self.new_master_password != self.old_master_password
+    }
+
+    fn clear_master_password_change(&mut self) {
+        self.old_master_password.clear();
+        self.new_master_password.clear();
+        self.new_master_password2.clear();
+        self.mp_change_in_progress = false;
+        self.password_changes = None;
+        self.mp_wrong = false;
+    }
+
+    fn backup_password_looks_valid(&self) -> bool {
+        (self.backup_password.is_empty() || self.backup_password.len() >= 8)
+            && self.backup_password == self.backup_password2
+    }
+
+    fn clear_backup(&mut self) {
+        self.backup_password.clear();
+        self.backup_password2.clear();
+        self.backup_in_progress = false;
+        self.backup_msg = None;
+        self.import_preview = None;
+    }
+
+    fn clear_password(&mut self) {
+        self.password_msg.clear();
+        self.username_msg = None;
+        self.password_revealed = false;
+        self.password_timer = None;
     }
 
     fn can_process_alias(&self) -> bool {
@@ -157,33 +438,79 @@ impl App {
         }
     }
 
-    fn collect_aliases(&self) -> Vec<String> {
-        let psh = self.psh.get().unwrap();
-        psh.aliases()
-            .iter()
-            .map(|x| x.to_string())
-            .collect()
+    fn apply_settings(&mut self) {
+        self.options_visible = self.settings.options_visible;
+        #[cfg(feature = "keyboard")]
+        {
+            self.kb_visible = self.settings.kb_visible;
+        }
+        if self.alias.is_empty() {
+            self.reset_alias_options();
+        }
+    }
+
+    fn save_settings(&self) {
+        let settings = self.settings.clone();
+        spawn_local(async move {
+            if let Err(e) = settings::save(&settings).await {
+                log(&format!("Failed to save settings: {}", e));
+            }
+        });
     }
 
-    fn initialize_psh(&self, cb: Callback<Option<Psh>>) {
-        let master_password = self.master_password.clone();
+    // Sets alias options to their defaults
+    fn reset_alias_options(&mut self) {
+        self.alias_handle = alias_handle_by_id(self.settings.alias_handle);
+        self.alias_handle_user_choice = self.alias_handle;
+        self.charset = charset_by_id(self.settings.charset).unwrap_or(CharSet::Standard);
+        self.charset_user_choice = self.charset;
+        self.length = None;
+        self.length_user_choice = None;
+        self.passphrase = None;
+        self.passphrase_user_choice = None;
+        self.site_rule = None;
+        self.generation = 0;
+    }
+
+    fn is_unlocked(&self) -> bool {
+        !matches!(self.state, AppState::New | AppState::Unlocking | AppState::ResettingDb)
+    }
+
+    // Keeps unencrypted list of alias names up to date (or empty if user opted out)
+    fn save_alias_names(&self) {
+        let names = if self.settings.keep_alias_names { self.known_alias_names() } else { Vec::new() };
         spawn_local(async move {
-            // XXX: The process is very resource-intensive and freezes Yew completely so
-            // we give Yew 10 milliseconds to update UI before it becomes unresponsive :(
-            sleep(Duration::from_millis(10)).await;
-
-            let res = Psh::new(
-                ZeroizingString::new(master_password),
-                PshWebDb::new(),
-            );
-            if let Ok(psh) = res {
-                cb.emit(Some(psh));
-            } else {
-                log(&format!("Failed to initialize Psh: {}", res.err().unwrap()));
-                cb.emit(None);
+            if let Err(e) = alias_names::save(&names).await {
+                log(&format!("Failed to save alias names: {}", e));
             }
         });
     }
+
+    // Restarts idle lock countdown
+    fn restart_idle_timeout(&mut self, ctx: &Context<Self>) {
+        self.idle_timeout = self.settings.idle_timeout_sec.map(|sec| {
+            let scope = ctx.link().clone();
+            Timeout::new(sec * 1_000, move || scope.send_message(Msg::Lock))
+        });
+    }
+
+    fn known_alias_names(&self) -> Vec<String> {
+        self.known_aliases.iter()
+            .map(|x| x.alias.clone())
+            .collect()
+    }
+
+    // Aliases that hold stored passwords instead of derived ones
+    fn stored_alias_names(&self) -> Vec<String> {
+        self.known_aliases.iter()
+            .filter(|x| x.stored)
+            .map(|x| x.alias.clone())
+            .collect()
+    }
+
+    fn alias_info(&self, alias: &str) -> Option<&AliasInfo> {
+        self.known_aliases.iter().find(|x| x.alias == alias)
+    }
 }
 
 impl Component for App {
@@ -215,25 +542,105 @@ impl Component for App {
             "resume",
             move |e| on_resume.emit(e.clone())
         );
-        let listeners = AppEventListeners { _pause: pause, _resume: resume };
+        let keydown = {
+            let scope = ctx.link().clone();
+            EventListener::new(&document, "keydown", move |e| {
+                let e = e.dyn_ref::<KeyboardEvent>().unwrap();
+                if is_lock_shortcut(e) {
+                    e.prevent_default();
+                    scope.send_message(Msg::Lock);
+                } else {
+                    scope.send_message(Msg::OnUserActivity);
+                }
+            })
+        };
+        let pointerdown = {
+            let scope = ctx.link().clone();
+            EventListener::new(&document, "pointerdown", move |_| {
+                scope.send_message(Msg::OnUserActivity);
+            })
+        };
+        let listeners = AppEventListeners {
+            _pause: pause,
+            _resume: resume,
+            _keydown: keydown,
+            _pointerdown: pointerdown,
+        };
+
+        if is_tauri() {
+            on_window_minimized(ctx.link().callback(|_| Msg::OnWindowMinimized));
+        }
+
+        {
+            let scope = ctx.link().clone();
+            spawn_local(async move {
+                scope.send_message(Msg::OnSettingsLoaded(settings::load().await));
+                scope.send_message(Msg::OnLockoutLoaded(lockout::load().await));
+                scope.send_message(Msg::OnFingerprintKeyLoaded(fingerprint::load_key().await));
+            });
+        }
+
+        let backend = Backend::new(ctx.link().callback(Msg::OnBackendResponse));
+        backend.send(PshRequest::CheckDb);
 
         Self {
             state: AppState::New,
-            psh: OnceCell::new(),
+            backend,
+            db_exists: false,
+            unlock_timer: None,
+            unlock_elapsed: 0,
+            lockout: Lockout::default(),
+            lockout_countdown: 0,
+            lockout_timer: None,
+            db_wiped: false,
+            unlock_error: None,
+            db_reset_requested: false,
+            restore_backup: false,
+            recovery_alias_names: Vec::new(),
+            recovery_msg: None,
+            settings: Settings::default(),
             lock_timeout: None::<Timeout>,
-            master_password: String::new(),
-            master_password2: String::new(),
+            idle_timeout: None,
+            master_password: SecretString::default(),
+            master_password2: SecretString::default(),
             mp_wrong: false,
+            fingerprint_key: None,
+            mp_strength: None,
+            weak_mp_confirming: false,
+            weak_mp_accepted: false,
+            old_master_password: SecretString::default(),
+            new_master_password: SecretString::default(),
+            new_master_password2: SecretString::default(),
+            mp_change_in_progress: false,
+            password_changes: None,
+            backup_password: SecretString::default(),
+            backup_password2: SecretString::default(),
+            backup_in_progress: false,
+            backup_msg: None,
+            import_preview: None,
             known_aliases: Vec::new(),
             alias: String::new(),
             alias_handle: AliasHandle::Store,
             alias_handle_user_choice: AliasHandle::Store,
             use_secret: true,
-            secret: String::new(),
+            secret: SecretString::default(),
             charset: CharSet::Standard,
             charset_user_choice: CharSet::Standard,
+            length: None,
+            length_user_choice: None,
+            passphrase: None,
+            passphrase_user_choice: None,
+            site_rule: None,
+            generation: 0,
             known_alias: false,
-            password_msg: String::new(),
+            password_msg: SecretString::default(),
+            username_msg: None,
+            password_revealed: false,
+            password_countdown: 0,
+            password_timer: None,
+            clipboard_password: None,
+            clipboard_countdown: 0,
+            clipboard_timer: None,
             password_ref: NodeRef::default(),
             input_ref: NodeRef::default(),
             options_visible: false,
@@ -247,26 +654,57 @@ impl Component for App {
         match msg {
             Msg::OnAppPause => {
                 if self.state != AppState::New {
-                    let scope = ctx.link().clone();
-                    let timeout = Timeout::new(LOCK_TIMEOUT_SEC * 1_000, move || {
-                        scope.send_message(Msg::Lock);
-                    });
-                    self.lock_timeout = Some(timeout);
+                    match self.settings.pause_timeout_sec {
+                        Some(0) => ctx.link().send_message(Msg::Lock),
+                        Some(sec) => {
+                            let scope = ctx.link().clone();
+                            let timeout = Timeout::new(sec * 1_000, move || {
+                                scope.send_message(Msg::Lock);
+                            });
+                            self.lock_timeout = Some(timeout);
+                        }
+                        None => {}
+                    }
                 }
             },
             Msg::OnAppResume => {
                 self.lock_timeout = None;
             }
+            Msg::OnWindowMinimized => {
+                if self.state != AppState::New && self.settings.lock_on_minimize {
+                    ctx.link().send_message(Msg::Lock);
+                }
+                // Do not update view
+                return false;
+            }
+            Msg::OnUserActivity => {
+                if self.is_unlocked() {
+                    self.restart_idle_timeout(ctx);
+                }
+                // Do not update view
+                return false;
+            }
             Msg::Lock => {
+                if self.state == AppState::Unlocking {
+                    self.backend.reset();
+                    self.unlock_timer = None;
+                } else {
+                    self.backend.send(PshRequest::Lock);
+                }
                 self.state = AppState::New;
-                self.psh = OnceCell::new();
-                self.master_password = String::new();
-                self.master_password2 = String::new();
+                self.master_password.clear();
+                self.master_password2.clear();
+                self.clear_master_password_change();
+                self.clear_backup();
                 self.known_aliases = Vec::new();
                 self.alias = String::new();
-                self.secret = String::new();
-                self.password_msg = String::new();
+                self.secret.clear();
+                self.clear_password();
                 self.lock_timeout = None;
+                self.idle_timeout = None;
+                if self.settings.clear_clipboard_on_lock {
+                    ctx.link().send_message(Msg::ClearClipboard);
+                }
             }
             Msg::OnFocusOut(e) => {
                 // Check where the focus goes. If on non-focusable element, then bring it back
@@ -285,32 +723,108 @@ impl Component for App {
                     }
                 }
             }
+            Msg::RevealPassword(reveal) => {
+                if self.password_revealed == reveal {
+                    // Do not update view
+                    return false;
+                }
+                self.password_revealed = reveal;
+            }
+            Msg::OnPasswordTick => {
+                self.password_countdown = self.password_countdown.saturating_sub(1);
+                if self.password_countdown == 0 {
+                    self.clear_password();
+                }
+            }
+            Msg::CopyPassword => {
+                let pass = self.password_msg.clone();
+                let scope = ctx.link().clone();
+                spawn_local(async move {
+                    match clipboard::write_text(&pass).await {
+                        Ok(()) => scope.send_message(Msg::OnPasswordCopied(pass)),
+                        Err(e) => log(&format!("Failed to copy password: {:?}", e)),
+                    }
+                });
+                // Do not update view
+                return false;
+            }
+            Msg::CopyUsername => {
+                let username = self.username_msg.clone().unwrap_or_default();
+                spawn_local(async move {
+                    if let Err(e) = clipboard::write_text(&username).await {
+                        log(&format!("Failed to copy username: {:?}", e));
+                    }
+                });
+                return false;
+            }
+            Msg::OnPasswordCopied(pass) => {
+                self.clipboard_password = Some(pass);
+                self.clipboard_countdown = self.settings.clipboard_clear_sec;
+                let scope = ctx.link().clone();
+                self.clipboard_timer = Some(Interval::new(1_000, move || {
+                    scope.send_message(Msg::OnClipboardTick);
+                }));
+            }
+            Msg::OnClipboardTick => {
+                self.clipboard_countdown = self.clipboard_countdown.saturating_sub(1);
+                if self.clipboard_countdown == 0 {
+                    ctx.link().send_message(Msg::ClearClipboard);
+                }
+            }
+            Msg::ClearClipboard => {
+                self.clipboard_timer = None;
+                match self.clipboard_password.take() {
+                    Some(pass) => spawn_local(async move {
+                        if let Err(e) = clipboard::clear_if_holds(&pass).await {
+                            log(&format!("Failed to clear clipboard: {:?}", e));
+                        }
+                    }),
+                    // Do not update view
+                    None => return false,
+                }
+            }
             Msg::OnInputFocus(new_input_ref) => {
                 // Since we move focus after processing (and call this handler),
                 // we can check `input_ref` and clear last derived password when
                 // input gets focus
                 if self.input_ref.get().is_none() {
-                    self.password_msg.clear()
+                    self.clear_password();
                 }
                 self.input_ref = new_input_ref;
             }
             Msg::OnPasswordInput(input) => {
-                self.master_password = input;
-                self.mp_wrong = false;
+                self.master_password.edit(input);
+                self.update_mp_strength();
+                if self.unlock_error == Some(UnlockError::WrongPassword) {
+                    self.unlock_error = None;
+                }
             }
             Msg::OnPassword2Input(input) => {
-                self.master_password2 = input;
+                self.master_password2.edit(input);
+            }
+            Msg::OverrideMpPolicy => {
+                // Weak master password is only accepted on second confirmation
+                if self.weak_mp_confirming {
+                    self.weak_mp_accepted = true;
+                } else {
+                    self.weak_mp_confirming = true;
+                }
+            }
+            Msg::CancelMpPolicyOverride => {
+                self.weak_mp_confirming = false;
             }
             Msg::OnAliasInput((input, known)) => {
                 self.alias = input.clone();
                 self.known_alias = known.clone();
                 if known {
                     self.alias_handle = AliasHandle::Store;
-                    let alias = ZeroizingString::new(input);
-                    let needs_secret = self.psh.get().unwrap().alias_uses_secret(&alias);
-                    self.use_secret = needs_secret;
-                    let alias_charset = self.psh.get().unwrap().get_charset(&alias);
-                    self.charset = alias_charset;
+                    let info = self.alias_info(&input).unwrap().clone();
+                    self.use_secret = info.use_secret;
+                    self.charset = charset_by_id(info.charset).unwrap_or(CharSet::Standard);
+                    self.length = info.length;
+                    self.passphrase = info.passphrase;
+                    self.generation = info.generation;
+                    self.site_rule = None;
                 }
                 else {
                     // Reset "remove" alias handle because it's only applicable to known aliases
@@ -320,17 +834,33 @@ impl Component for App {
                         self.alias_handle = self.alias_handle_user_choice;
                     }
                     self.use_secret = true;
-                    self.charset = self.charset_user_choice;
+                    self.generation = 0;
+                    let site_rule = site_rules::find(&input);
+                    match &site_rule {
+                        // Preset is applied once, so that user can override it
+                        Some(rule) if self.site_rule.as_ref() != Some(rule) => {
+                            self.charset = rule.charset();
+                            self.length = rule.length(&LENGTHS);
+                            self.passphrase = None;
+                        }
+                        Some(_) => {}
+                        None => {
+                            self.charset = self.charset_user_choice;
+                            self.length = self.length_user_choice;
+                            self.passphrase = self.passphrase_user_choice.clone();
+                        }
+                    }
+                    self.site_rule = site_rule;
                 }
             }
             Msg::OnSecretInput(input) => {
-                self.secret = input;
+                self.secret.edit(input);
             }
             Msg::OnEnterPressed => {
                 // TODO: move focus to the next sensible input if any
                 match self.state {
                     AppState::New => {
-                        if self.mp_looks_valid() {
+                        if self.can_login() {
                             ctx.link().send_message(Msg::Login);
                         } else {
                             // Do not update view
@@ -345,11 +875,153 @@ impl Component for App {
                             return false;
                         }
                     }
+                    AppState::ManagingAliases
+                    | AppState::StoringPassword
+                    | AppState::Backup
+                    | AppState::Settings
+                    | AppState::ResettingDb => {
+                        // Do not update view
+                        return false;
+                    }
+                    AppState::ChangingMasterPassword => {
+                        if self.password_changes.is_none()
+                            && !self.mp_change_in_progress
+                            && self.new_mp_looks_valid()
+                        {
+                            ctx.link().send_message(Msg::PreviewMasterPasswordChange);
+                        } else {
+                            // Do not update view
+                            return false;
+                        }
+                    }
                     AppState::Unlocking => unreachable!(),
                 }
             }
+            Msg::ShowAliasManager => {
+                self.state = AppState::ManagingAliases;
+            }
+            Msg::CloseAliasManager => {
+                self.state = AppState::Initialized;
+            }
+            Msg::ShowPasswordStore => {
+                self.state = AppState::StoringPassword;
+            }
+            Msg::StorePassword((alias, password)) => {
+                self.backend.send(PshRequest::StorePassword { alias, password: password.expose() });
+                self.alias.clear();
+                self.known_alias = false;
+                self.reset_alias_options();
+                self.state = AppState::Initialized;
+            }
+            Msg::ClosePasswordStore => {
+                self.state = AppState::Initialized;
+            }
+            Msg::UpdateAlias((alias, new)) => {
+                self.backend.send(PshRequest::UpdateAlias { alias, new });
+            }
+            Msg::RemoveAliases(aliases) => {
+                self.backend.send(PshRequest::RemoveAliases { aliases });
+            }
+            Msg::OnSettingsLoaded(settings) => {
+                self.settings = settings;
+                self.apply_settings();
+            }
+            Msg::ShowSettings => {
+                self.state = AppState::Settings;
+            }
+            Msg::CloseSettings => {
+                self.state = AppState::Initialized;
+            }
+            Msg::SaveSettings(settings) => {
+                let keep_alias_names_changed = settings.keep_alias_names != self.settings.keep_alias_names;
+                self.settings = settings;
+                self.apply_settings();
+                self.save_settings();
+                if keep_alias_names_changed {
+                    self.save_alias_names();
+                }
+                if self.is_unlocked() {
+                    self.restart_idle_timeout(ctx);
+                }
+                self.state = AppState::Initialized;
+            }
+            Msg::ShowBackup => {
+                self.state = AppState::Backup;
+            }
+            Msg::CloseBackup => {
+                self.clear_backup();
+                self.state = AppState::Initialized;
+            }
+            Msg::OnBackupPasswordInput(input) => {
+                self.backup_password.edit(input);
+                self.backup_msg = None;
+            }
+            Msg::OnBackupPassword2Input(input) => {
+                self.backup_password2.edit(input);
+            }
+            Msg::Export => {
+                let passphrase = if self.backup_password.is_empty() {
+                    None
+                } else {
+                    Some(self.backup_password.expose())
+                };
+                self.backup_in_progress = true;
+                self.backup_msg = None;
+                self.backend.send(PshRequest::Export { passphrase });
+            }
+            Msg::Import => {
+                // No progress indication here as user may silently dismiss file picker
+                self.backup_msg = None;
+                self.backend.send(PshRequest::Import {
+                    passphrase: self.backup_password.expose(),
+                });
+            }
+            Msg::ApplyImport((new, replace)) => {
+                self.backup_in_progress = true;
+                self.import_preview = None;
+                self.backend.send(PshRequest::ApplyImport { new, replace });
+            }
+            Msg::CancelImport => {
+                self.import_preview = None;
+            }
+            Msg::ShowMasterPasswordChange => {
+                self.state = AppState::ChangingMasterPassword;
+            }
+            Msg::CloseMasterPasswordChange => {
+                if self.password_changes.is_some() {
+                    self.backend.send(PshRequest::CancelMasterPasswordChange);
+                }
+                self.clear_master_password_change();
+                self.state = AppState::Initialized;
+            }
+            Msg::OnOldPasswordInput(input) => {
+                self.old_master_password.edit(input);
+                self.mp_wrong = false;
+            }
+            Msg::OnNewPasswordInput(input) => {
+                self.new_master_password.edit(input);
+            }
+            Msg::OnNewPassword2Input(input) => {
+                self.new_master_password2.edit(input);
+            }
+            Msg::PreviewMasterPasswordChange => {
+                self.mp_change_in_progress = true;
+                self.backend.send(PshRequest::PreviewMasterPasswordChange {
+                    old_password: self.old_master_password.expose(),
+                    new_password: self.new_master_password.expose(),
+                });
+                self.old_master_password.clear();
+                self.new_master_password.clear();
+                self.new_master_password2.clear();
+            }
+            Msg::ConfirmMasterPasswordChange => {
+                self.mp_change_in_progress = true;
+                self.backend.send(PshRequest::ConfirmMasterPasswordChange);
+            }
             Msg::OnOptionsCollapsibleClick(visible) => {
                 self.options_visible = visible;
+                self.settings.options_visible = visible;
+                self.save_settings();
             }
             Msg::SetAliasHandle(value) => {
                 match value.as_str() {
@@ -385,70 +1057,315 @@ impl Component for App {
                     _ => unreachable!()
                 }
             }
+            Msg::SetLength(value) => {
+                self.length = LENGTHS[value.parse::<usize>().unwrap()];
+                self.length_user_choice = self.length;
+            }
+            Msg::SetWordCount(value) => {
+                let words = WORD_COUNTS[value.parse::<usize>().unwrap()];
+                self.passphrase = with_word_count(self.passphrase.as_ref(), words);
+                self.passphrase_user_choice = self.passphrase.clone();
+            }
+            Msg::SetSeparator(value) => {
+                if let Some(passphrase) = self.passphrase.as_mut() {
+                    passphrase.separator = SEPARATORS[value.parse::<usize>().unwrap()].0.to_string();
+                }
+                self.passphrase_user_choice = self.passphrase.clone();
+            }
+            Msg::SetGeneration(value) => {
+                self.generation = value.parse().unwrap();
+            }
+            Msg::RotateAlias | Msg::RollBackAlias => {
+                let Some(info) = self.alias_info(self.alias.trim()) else {
+                    return false;
+                };
+                let new = if let Msg::RotateAlias = msg {
+                    info.rotated()
+                } else {
+                    info.rolled_back()
+                };
+                self.generation = new.generation;
+                self.backend.send(PshRequest::UpdateAlias { alias: new.alias.clone(), new });
+                self.clear_password();
+            }
             Msg::Login => {
                 self.state = AppState::Unlocking;
+                self.unlock_elapsed = 0;
+                let scope = ctx.link().clone();
+                self.unlock_timer = Some(Interval::new(1_000, move || {
+                    scope.send_message(Msg::OnUnlockTick);
+                }));
+                self.backend.send(PshRequest::Unlock {
+                    master_password: self.master_password.expose(),
+                });
             }
-            Msg::Initialize(maybe_psh) => {
-                if let Some(psh) = maybe_psh {
-                    self.state = AppState::Initialized;
-                    self.psh.set(psh).ok();
-                    self.known_aliases = self.collect_aliases();
-                } else {
-                    self.state = AppState::New;
-                    self.mp_wrong = true;
+            Msg::OnUnlockTick => {
+                self.unlock_elapsed += 1;
+            }
+            Msg::OnLockoutLoaded(lockout) => {
+                self.lockout = lockout;
+                self.start_lockout_countdown(ctx);
+            }
+            Msg::RetryDbCheck => {
+                self.unlock_error = None;
+                self.backend.send(PshRequest::CheckDb);
+            }
+            Msg::ShowDbReset(restore_backup) => {
+                self.state = AppState::ResettingDb;
+                self.restore_backup = restore_backup;
+                // Built-in keyboard isn't used for confirmation phrase
+                self.input_ref = NodeRef::default();
+                let scope = ctx.link().clone();
+                spawn_local(async move {
+                    scope.send_message(Msg::OnAliasNamesLoaded(alias_names::load().await));
+                });
+            }
+            Msg::CloseDbReset => {
+                self.state = AppState::New;
+                self.restore_backup = false;
+                self.recovery_alias_names = Vec::new();
+                self.recovery_msg = None;
+            }
+            Msg::OnAliasNamesLoaded(names) => {
+                self.recovery_alias_names = names;
+            }
+            Msg::SaveAliasNames => {
+                let contents = self.recovery_alias_names.join("\n");
+                let scope = ctx.link().clone();
+                spawn_local(async move {
+                    let res = save_text_file(ALIAS_NAMES_FILE_NAME, contents).await;
+                    scope.send_message(Msg::OnAliasNamesSaved(res));
+                });
+                // Do not update view
+                return false;
+            }
+            Msg::OnAliasNamesSaved(res) => {
+                self.recovery_msg = match res {
+                    Ok(true) => Some("Alias names have been saved".to_string()),
+                    Ok(false) => return false,
+                    Err(e) => Some(format!("Failed to save alias names: {}", e)),
+                };
+            }
+            Msg::ResetDb => {
+                self.db_reset_requested = true;
+                self.backend.send(PshRequest::WipeDb);
+            }
+            Msg::OnFingerprintKeyLoaded(key) => {
+                self.fingerprint_key = key;
+            }
+            Msg::OnLockoutTick => {
+                self.lockout_countdown = self.lockout.seconds_left();
+                if self.lockout_countdown == 0 {
+                    self.lockout_timer = None;
+                }
+            }
+            Msg::CancelUnlock => {
+                // Worker is busy hashing and can't be interrupted, so we just abandon it
+                self.backend.reset();
+                self.unlock_timer = None;
+                self.state = AppState::New;
+            }
+            Msg::OnBackendResponse(response) => {
+                match response {
+                    PshResponse::DbExists(exists) => {
+                        self.db_exists = exists;
+                    }
+                    PshResponse::Unlocked(aliases) => {
+                        // Unlock could have been cancelled in the meantime
+                        if self.state != AppState::Unlocking {
+                            return false;
+                        }
+                        self.state = AppState::Initialized;
+                        self.db_exists = true;
+                        self.known_aliases = aliases;
+                        self.unlock_timer = None;
+                        self.master_password.clear();
+                        self.master_password2.clear();
+                        self.restart_idle_timeout(ctx);
+                        self.db_wiped = false;
+                        if self.settings.keep_alias_names {
+                            self.save_alias_names();
+                        }
+                        self.unlock_error = None;
+                        if self.restore_backup {
+                            self.restore_backup = false;
+                            self.state = AppState::Backup;
+                        }
+                        if self.lockout.failures > 0 {
+                            self.lockout.reset();
+                            self.save_lockout();
+                        }
+                    }
+                    PshResponse::UnlockFailed(e) => {
+                        if self.state != AppState::Unlocking {
+                            return false;
+                        }
+                        log(&format!("Failed to initialize Psh: {:?}", e));
+                        self.state = AppState::New;
+                        self.unlock_timer = None;
+                        self.master_password.clear();
+                        let wrong_password = e == UnlockError::WrongPassword;
+                        self.unlock_error = Some(e);
+                        // Database failures aren't guessing attempts
+                        if wrong_password {
+                            self.lockout.record_failure();
+                            match self.settings.wipe_after_failures {
+                                Some(limit) if self.lockout.failures >= limit => {
+                                    self.backend.send(PshRequest::WipeDb);
+                                }
+                                _ => {
+                                    self.save_lockout();
+                                    self.start_lockout_countdown(ctx);
+                                }
+                            }
+                        }
+                    }
+                    PshResponse::DbWiped => {
+                        self.db_exists = false;
+                        // Database is either reset by user or wiped after failed attempts
+                        self.db_wiped = !self.db_reset_requested;
+                        self.db_reset_requested = false;
+                        self.unlock_error = None;
+                        self.known_aliases.clear();
+                        // Names of deleted aliases mustn't outlive them
+                        self.save_alias_names();
+                        self.lockout.reset();
+                        self.save_lockout();
+                        self.start_lockout_countdown(ctx);
+                        self.master_password.clear();
+                        self.master_password2.clear();
+                        self.update_mp_strength();
+                        self.recovery_alias_names = Vec::new();
+                        self.recovery_msg = None;
+                        // Start over as on the first run
+                        self.state = AppState::New;
+                    }
+                    PshResponse::Password(pass) => {
+                        self.password_msg = pass.into();
+                        self.password_revealed = false;
+                        self.password_countdown = self.settings.password_expiry_sec;
+                        let scope = ctx.link().clone();
+                        self.password_timer = Some(Interval::new(1_000, move || {
+                            scope.send_message(Msg::OnPasswordTick);
+                        }));
+                    }
+                    PshResponse::Username(username) => {
+                        self.username_msg = Some(username);
+                    }
+                    PshResponse::Aliases(aliases) => {
+                        self.known_aliases = aliases;
+                        if self.settings.keep_alias_names {
+                            self.save_alias_names();
+                        }
+                        if self.backup_in_progress {
+                            self.backup_in_progress = false;
+                            self.backup_msg = Some("Aliases have been imported".to_string());
+                        }
+                    }
+                    PshResponse::MasterPasswordChangePreview(changes) => {
+                        self.mp_change_in_progress = false;
+                        self.password_changes = Some(changes);
+                    }
+                    PshResponse::MasterPasswordChanged => {
+                        self.clear_master_password_change();
+                        self.state = AppState::Initialized;
+                    }
+                    PshResponse::MasterPasswordWrong => {
+                        self.mp_change_in_progress = false;
+                        self.mp_wrong = true;
+                    }
+                    PshResponse::Exported(exported) => {
+                        self.backup_in_progress = false;
+                        if exported {
+                            self.backup_password.clear();
+                            self.backup_password2.clear();
+                            self.backup_msg = Some("Backup file has been saved".to_string());
+                        }
+                    }
+                    PshResponse::ImportPreview(preview) => {
+                        // App could have been locked in the meantime
+                        if self.state != AppState::Backup {
+                            return false;
+                        }
+                        self.backup_password.clear();
+                        self.backup_password2.clear();
+                        self.import_preview = Some(preview);
+                    }
+                    PshResponse::ImportFailed(e) => {
+                        self.backup_msg = Some(e);
+                    }
+                    PshResponse::ImportCancelled => {
+                        return false;
+                    }
+                    PshResponse::Failed(e) => {
+                        log(&e);
+                        if self.db_reset_requested {
+                            self.db_reset_requested = false;
+                            self.restore_backup = false;
+                            self.state = AppState::New;
+                            self.unlock_error = Some(UnlockError::StorageFailure(e));
+                            return true;
+                        }
+                        if self.backup_in_progress {
+                            self.backup_in_progress = false;
+                            self.backup_msg = Some(e);
+                            return true;
+                        }
+                        if !self.mp_change_in_progress {
+                            return false;
+                        }
+                        self.mp_change_in_progress = false;
+                    }
                 }
-                self.master_password.clear();
             }
             Msg::Process => {
                 // No input is in focus now
                 ctx.link().send_message(Msg::OnInputFocus(NodeRef::default()));
 
-                let psh = self.psh.get_mut().unwrap();
                 let alias_string = self.alias.trim().to_string();
                 let secret_string =
-                    if self.secret.to_string().is_empty() || !self.use_secret {
+                    if self.secret.is_empty() || !self.use_secret {
                         None
                     } else {
-                        Some(ZeroizingString::new(self.secret.to_string()))
+                        Some(self.secret.expose())
                     };
                 let needs_secret = secret_string.is_some();
                 if self.alias_handle != AliasHandle::Remove {
-                    let pass = psh.derive_password(
-                        &ZeroizingString::new(alias_string.clone()),
-                        secret_string,
-                        Some(self.charset),
-                    );
-                    self.password_msg = pass.to_string();
-                    if !self.known_aliases.contains(&alias_string)
+                    self.backend.send(PshRequest::DerivePassword {
+                        alias: alias_string.clone(),
+                        secret: secret_string,
+                        charset: charset_id(self.charset),
+                        length: self.length,
+                        generation: self.generation,
+                        passphrase: self.passphrase.clone(),
+                    });
+                    self.username_msg = None;
+                    match self.alias_info(&alias_string).and_then(|info| info.username.clone()) {
+                        Some(Username::Plain(username)) => self.username_msg = Some(username),
+                        Some(Username::Derived) => {
+                            self.backend.send(PshRequest::DeriveUsername { alias: alias_string.clone() });
+                        }
+                        None => {}
+                    }
+                    if self.alias_info(&alias_string).is_none()
                         && self.alias_handle == AliasHandle::Store
                     {
-                        let res = psh.append_alias_to_db(
-                            &ZeroizingString::new(alias_string.clone()),
-                            Some(needs_secret),
-                            Some(self.charset),
-                        );
-                        if res.is_ok() {
-                            self.known_aliases = self.collect_aliases();
-                        } else {
-                            log("Failed to save alias");
-                        }
+                        self.backend.send(PshRequest::AppendAlias {
+                            alias: alias_string,
+                            use_secret: needs_secret,
+                            charset: charset_id(self.charset),
+                            length: self.length,
+                            passphrase: self.passphrase.clone(),
+                        });
                     }
                 } else {
-                    let res = psh.remove_alias_from_db(&ZeroizingString::new(alias_string.clone()));
-                    if res.is_ok() {
-                        self.known_aliases = self.collect_aliases();
-                    } else {
-                        log("Failed to remove alias");
-                    }
+                    self.backend.send(PshRequest::RemoveAlias { alias: alias_string });
                 }
                 self.alias.clear();
                 self.secret.clear();
                 self.use_secret = true;
                 self.known_alias = false;
-                self.alias_handle = AliasHandle::Store;
-                self.alias_handle_user_choice = AliasHandle::Store;
-                self.charset = CharSet::Standard;
-                self.charset_user_choice = CharSet::Standard;
+                self.reset_alias_options();
                 // Focus on Password element to move focus away from button
                 let el = self.password_ref.cast::<web_sys::HtmlElement>().unwrap();
                 el.focus().unwrap();
@@ -456,37 +1373,51 @@ impl Component for App {
             #[cfg(feature = "keyboard")]
             Msg::OnKbCollapsibleClick(visible) => {
                 self.kb_visible = visible;
+                self.settings.kb_visible = visible;
+                self.save_settings();
             }
             #[cfg(feature = "keyboard")]
             Msg::OnKbInput(value) => {
                 if self.input_ref.get().is_some() {
                     let input = self.input_ref.cast::<web_sys::HtmlInputElement>().unwrap();
+                    let id = input.id();
 
-                    // Fill input with new value
-                    let new_value =
+                    if id == "alias-input" {
+                        // Fill input with new value
+                        let mut new_value = input.value();
                         if !value.is_empty() {
-                            input.value() + &value
+                            new_value.push_str(&value);
                         } else {
                             // Keyboard sends empty strings on Backspace key presses
-                            let mut new_value = input.value();
                             new_value.pop();
-                            new_value
-                        };
-                    input.set_value(&new_value);
-
-                    // Find relative variable in store and change it as well
-                    let id = input.id();
-                    match id.as_str() {
-                        "mp-input" => ctx.link().send_message(Msg::OnPasswordInput(new_value)),
-                        "mp2-input" => ctx.link().send_message(Msg::OnPassword2Input(new_value)),
-                        "alias-input" => {
-                            let new_value = new_value.trim().to_string();
-                            let known = self.known_aliases.contains(&new_value);
-                            ctx.link().send_message(Msg::OnAliasInput((new_value, known)));
                         }
-                        "secret-input" => ctx.link().send_message(Msg::OnSecretInput(new_value)),
-                        _ => unimplemented!()
+                        input.set_value(&new_value);
+
+                        let new_value = new_value.trim().to_string();
+                        let known = self.alias_info(&new_value).is_some();
+                        ctx.link().send_message(Msg::OnAliasInput((new_value, known)));
+                        return true;
                     }
+
+                    // Secrets go straight to their variables in store,
+                    // secret inputs only show masks
+                    let edit = if !value.is_empty() {
+                        SecretEdit::Insert(value.into())
+                    } else {
+                        SecretEdit::DeleteBackward
+                    };
+                    let msg = match id.as_str() {
+                        "mp-input" => Msg::OnPasswordInput(edit),
+                        "mp2-input" => Msg::OnPassword2Input(edit),
+                        "secret-input" => Msg::OnSecretInput(edit),
+                        "old-mp-input" => Msg::OnOldPasswordInput(edit),
+                        "new-mp-input" => Msg::OnNewPasswordInput(edit),
+                        "new-mp2-input" => Msg::OnNewPassword2Input(edit),
+                        "backup-pass-input" => Msg::OnBackupPasswordInput(edit),
+                        "backup-pass2-input" => Msg::OnBackupPassword2Input(edit),
+                        _ => unimplemented!()
+                    };
+                    ctx.link().send_message(msg);
                 }
             }
         }
@@ -499,6 +1430,7 @@ impl Component for App {
             <>
                 <Collapsible
                     name="keyboard"
+                    start_collapsed={!self.kb_visible}
                     on_click={ctx.link().callback(Msg::OnKbCollapsibleClick)}
                 />
                 <Keyboard
@@ -516,38 +1448,145 @@ impl Component for App {
         let keyboard_use = false;
 
         let spinner_overlay = {
-            let db_exists = PshWebDb::new().exists();
+            let db_exists = self.db_exists;
 
             html! {
                 <div class="overlay">
                     <div class="spinner"/>
                     <div>{ if db_exists {"Unlocking..."} else {"Initializing..."} }</div>
+                    <div>{ format!("{} s", self.unlock_elapsed) }</div>
+                    <div class="element">
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::CancelUnlock)}
+                        >
+                            { "Cancel" }
+                        </button>
+                    </div>
                 </div>
             }
         };
 
         let entrance_view = {
-            let db_exists = PshWebDb::new().exists();
+            let db_exists = self.db_exists;
 
             let maybe_warning = {
-                let class = if db_exists {
-                    let maybe_invisible = if self.mp_wrong { None } else { Some("invisible") };
-                    classes!("element", maybe_invisible)
+                let class = if db_exists && self.unlock_error.is_none() {
+                    classes!("element", "invisible")
                 } else {
                     classes!("element")
                 };
-                let message = if db_exists {
-                    "Wrong master password"
-                } else {
-                    "Warning: if you forget your Master Password
-                        you won't be able to retrieve your passwords"
+                let message = match &self.unlock_error {
+                    Some(e) => unlock_error_message(e),
+                    None if db_exists => "Wrong master password".to_string(),
+                    None => "Warning: if you forget your Master Password
+                        you won't be able to retrieve your passwords".to_string(),
                 };
                 html! { <div class={class}>{ message }</div> }
             };
 
+            let maybe_strength = match &self.mp_strength {
+                Some(strength) if !db_exists => {
+                    let score = strength.score as usize;
+                    let min_score = self.settings.min_mp_score as usize;
+                    // Policy matters only once password is otherwise fine
+                    let policy_violated = !self.mp_strong_enough()
+                        && !self.weak_mp_accepted
+                        && self.master_password.len() >= 8
+                        && self.master_password == self.master_password2;
+                    html! {
+                        <>
+                            <div class="element strength">
+                                <meter min="0" max="4" low="2" high="3" optimum="4"
+                                    value={score.to_string()}
+                                />
+                                <span>{ format!("{} (basic check)", SCORE_LABELS[score]) }</span>
+                            </div>
+                            if let Some(warning) = strength.warning {
+                                <div class="element">{ warning }</div>
+                            }
+                            if policy_violated && !self.weak_mp_confirming {
+                                <div class="element">
+                                    { format!("Master password should be at least \"{}\"", SCORE_LABELS[min_score]) }
+                                </div>
+                                <div class="element">
+                                    <button type="button"
+                                        onclick={ctx.link().callback(|_| Msg::OverrideMpPolicy)}
+                                    >
+                                        { "Use it anyway" }
+                                    </button>
+                                </div>
+                            }
+                            if policy_violated && self.weak_mp_confirming {
+                                <div class="element">
+                                    { "Weak master password makes all derived passwords easier \
+                                        to crack once Psh database leaks. Are you sure?" }
+                                </div>
+                                <div class="element">
+                                    <button type="button"
+                                        onclick={ctx.link().callback(|_| Msg::OverrideMpPolicy)}
+                                    >
+                                        { "Yes, use weak password" }
+                                    </button>
+                                    <button type="button"
+                                        onclick={ctx.link().callback(|_| Msg::CancelMpPolicyOverride)}
+                                    >
+                                        { "Cancel" }
+                                    </button>
+                                </div>
+                            }
+                        </>
+                    }
+                }
+                _ => html! {},
+            };
+
+            // Broken database can only be reset with the same confirmation as forgotten
+            // master password. Database of newer app version is left for the update.
+            let recovery_actions = match self.unlock_error {
+                Some(UnlockError::CorruptDb(_)) => html! {
+                    <div class="element">
+                        <button type="button" onclick={ctx.link().callback(|_| Msg::ShowDbReset(true))}>
+                            { "Restore from backup" }
+                        </button>
+                        <button type="button" onclick={ctx.link().callback(|_| Msg::ShowDbReset(false))}>
+                            { "Reset database" }
+                        </button>
+                    </div>
+                },
+                Some(UnlockError::StorageFailure(_) | UnlockError::Other(_)) => html! {
+                    <div class="element">
+                        <button type="button" onclick={ctx.link().callback(|_| Msg::RetryDbCheck)}>
+                            { "Retry" }
+                        </button>
+                    </div>
+                },
+                _ => html! {},
+            };
+            let newer_db = matches!(self.unlock_error, Some(UnlockError::UnsupportedDbVersion(_)));
+
+            let maybe_lockout_msg = {
+                let attempts_left = self.settings.wipe_after_failures
+                    .map(|limit| limit.saturating_sub(self.lockout.failures));
+                let message = if self.lockout_countdown > 0 {
+                    Some(format!("Too many failed attempts. Try again in {} s", self.lockout_countdown))
+                } else if self.db_wiped {
+                    Some("Aliases were wiped after too many failed unlock attempts".to_string())
+                } else if db_exists && self.lockout.failures > 0 {
+                    attempts_left.map(|n| format!("{} attempts left before aliases are wiped", n))
+                } else {
+                    None
+                };
+                html! {
+                    if let Some(message) = message {
+                        <div class="element">{ message }</div>
+                    }
+                }
+            };
+
             html! {
                 <>
                     { maybe_warning }
+                    { maybe_lockout_msg }
                     <SecretInput
                         text={self.master_password.clone()}
                         focus=true
@@ -558,6 +1597,10 @@ impl Component for App {
                         on_focus={ctx.link().callback(Msg::OnInputFocus)}
                         on_enter={ctx.link().callback(|_| Msg::OnEnterPressed)}
                     />
+                    <Fingerprint
+                        text={self.master_password.clone()}
+                        hash_key={self.fingerprint_key}
+                    />
                     if !db_exists {
                         <SecretInput
                             text={self.master_password2.clone()}
@@ -569,24 +1612,83 @@ impl Component for App {
                             on_enter={ctx.link().callback(|_| Msg::OnEnterPressed)}
                         />
                     }
+                    { maybe_strength }
                     <div class="element">
                         <button type="button"
                             onclick={ctx.link().callback(|_| Msg::Login)}
-                            disabled={!self.mp_looks_valid()}
+                            disabled={!self.can_login()}
                         >
                             { if db_exists { "Unlock" } else { "Start using Psh"} }
                         </button>
                     </div>
+                    { recovery_actions }
+                    if db_exists && !newer_db {
+                        <div class="element">
+                            <button type="button" onclick={ctx.link().callback(|_| Msg::ShowDbReset(false))}>
+                                { "Forgot master password?" }
+                            </button>
+                        </div>
+                    }
                 </>
             }
         };
 
+        let maybe_generations = match self.alias_info(self.alias.trim()) {
+            Some(info) if self.known_alias && !info.stored && self.alias_handle != AliasHandle::Remove => {
+                let current = info.generation;
+                // Generations rolled back from are kept, so that user can switch forward again
+                let latest = info.max_generation;
+                html! {
+                    <div class="element generations">
+                        <select
+                            title="Password generation"
+                            onchange={ctx.link().callback(|e: Event| {
+                                let select = e.target_dyn_into::<web_sys::HtmlSelectElement>().unwrap();
+                                Msg::SetGeneration(select.value())
+                            })}
+                        >
+                        {
+                            for (0..=latest).rev().map(|generation| html! {
+                                <option value={generation.to_string()} selected={generation == self.generation}>
+                                {
+                                    if generation == current {
+                                        format!("Generation {} (current)", generation + 1)
+                                    } else {
+                                        format!("Generation {}", generation + 1)
+                                    }
+                                }
+                                </option>
+                            })
+                        }
+                        </select>
+                        <button type="button" class="small"
+                            title="Switch to a new password, e.g. when site requires to change it"
+                            onclick={ctx.link().callback(|_| Msg::RotateAlias)}
+                        >
+                            { "Rotate" }
+                        </button>
+                        <button type="button" class="small"
+                            title="Switch back to the previous password"
+                            onclick={ctx.link().callback(|_| Msg::RollBackAlias)}
+                            disabled={current == 0}
+                        >
+                            { "Roll back" }
+                        </button>
+                    </div>
+                }
+            }
+            _ => html! {},
+        };
+
         let main_view = {
+            let password_revealed = self.password_revealed;
+            let password_actions_class = if self.password_msg.is_empty() { Some("invisible") } else { None };
             html! {
                 <>
                     <AliasInput
                         text={self.alias.clone()}
-                        known_aliases={self.known_aliases.clone()}
+                        known_aliases={self.known_alias_names()}
+                        stored_aliases={self.stored_alias_names()}
                         keyboard={keyboard_use}
                         on_input={ctx.link().callback(Msg::OnAliasInput)}
                         on_focus={ctx.link().callback(Msg::OnInputFocus)}
@@ -602,6 +1704,15 @@ impl Component for App {
                         on_focus={ctx.link().callback(Msg::OnInputFocus)}
                         on_enter={ctx.link().callback(|_| Msg::OnEnterPressed)}
                     />
+                    if let Some(rule) = &self.site_rule {
+                        <div class="element">
+                            { format!("Options are preset for {}: {}", rule.domain, rule.describe()) }
+                            if rule.may_fail() {
+                                { ". Derived password may still lack required characters or contain symbols the site doesn't accept" }
+                            }
+                        </div>
+                    }
+                    { maybe_generations }
                     <div class="element">
                         <button type="button"
                             onclick={ctx.link().callback(|_| Msg::Process)}
@@ -613,11 +1724,57 @@ impl Component for App {
                             }
                         </button>
                     </div>
-                    <div class="element password" ref={self.password_ref.clone()} tabindex="-1">
-                        <strong>{ &self.password_msg }</strong>
+                    <div class="element password" ref={self.password_ref.clone()} tabindex="-1"
+                        onpointerdown={ctx.link().callback(|_| Msg::RevealPassword(true))}
+                        onpointerup={ctx.link().callback(|_| Msg::RevealPassword(false))}
+                        onpointerleave={ctx.link().callback(|_| Msg::RevealPassword(false))}
+                    >
+                        <strong>
+                        {
+                            if self.password_revealed {
+                                self.password_msg.to_string()
+                            } else {
+                                mask(&self.password_msg)
+                            }
+                        }
+                        </strong>
+                    </div>
+                    <div class={classes!("element", "password-actions", password_actions_class)}>
+                        <button type="button" class="small"
+                            onclick={ctx.link().callback(move |_| Msg::RevealPassword(!password_revealed))}
+                        >
+                            { if self.password_revealed { "Hide" } else { "Show" } }
+                        </button>
+                        <button type="button" class="small"
+                            onclick={ctx.link().callback(|_| Msg::CopyPassword)}
+                        >
+                            { "Copy" }
+                        </button>
+                        <span>{ format!("Password will be hidden in {} s", self.password_countdown) }</span>
+                    </div>
+                    if let Some(username) = &self.username_msg {
+                        <div class="element username">
+                            <span>{ "Username:" }</span>
+                            <strong>{ username }</strong>
+                            <button type="button" class="small"
+                                onclick={ctx.link().callback(|_| Msg::CopyUsername)}
+                            >
+                                { "Copy" }
+                            </button>
+                        </div>
+                    }
+                    <div class="element clipboard">
+                        if self.clipboard_timer.is_some() {
+                            <span>{ format!("Clipboard will be cleared in {} s", self.clipboard_countdown) }</span>
+                            <button type="button" class="small"
+                                onclick={ctx.link().callback(|_| Msg::ClearClipboard)}
+                            >
+                                { "Clear now" }
+                            </button>
+                        }
                     </div>
                     <Collapsible name="options"
-                        start_collapsed=true
+                        start_collapsed={!self.options_visible}
                         on_click={ctx.link().callback(Msg::OnOptionsCollapsibleClick)}
                     />
                     <Triswitch
@@ -644,6 +1801,243 @@ impl Component for App {
                             "Reduced".to_string()]}
                         on_switch={ctx.link().callback(Msg::SetCharset)}
                     />
+                    <div class={classes!("element", "passphrase", if self.options_visible { None } else { Some("hidden") })}>
+                        <select
+                            title="Derive password of characters or passphrase of words"
+                            disabled={self.known_alias}
+                            onchange={ctx.link().callback(|e: Event| {
+                                let select = e.target_dyn_into::<web_sys::HtmlSelectElement>().unwrap();
+                                Msg::SetWordCount(select.value())
+                            })}
+                        >
+                        {
+                            for WORD_COUNTS.iter().enumerate().map(|(i, words)| html! {
+                                <option value={i.to_string()}
+                                    selected={*words == self.passphrase.as_ref().map(|p| p.words)}
+                                >
+                                    { word_count_label(*words) }
+                                </option>
+                            })
+                        }
+                        </select>
+                        if let Some(passphrase) = &self.passphrase {
+                            <select
+                                title="Separator of passphrase words"
+                                disabled={self.known_alias}
+                                onchange={ctx.link().callback(|e: Event| {
+                                    let select = e.target_dyn_into::<web_sys::HtmlSelectElement>().unwrap();
+                                    Msg::SetSeparator(select.value())
+                                })}
+                            >
+                            {
+                                for SEPARATORS.iter().enumerate().map(|(i, (separator, label))| html! {
+                                    <option value={i.to_string()} selected={*separator == passphrase.separator}>
+                                        { label }
+                                    </option>
+                                })
+                            }
+                            </select>
+                        }
+                    </div>
+                    <div class={classes!("element", if self.options_visible && self.passphrase.is_none() { None } else { Some("hidden") })}>
+                        <select
+                            title="Length of derived password"
+                            disabled={self.known_alias}
+                            onchange={ctx.link().callback(|e: Event| {
+                                let select = e.target_dyn_into::<web_sys::HtmlSelectElement>().unwrap();
+                                Msg::SetLength(select.value())
+                            })}
+                        >
+                        {
+                            for LENGTHS.iter().enumerate().map(|(i, length)| html! {
+                                <option value={i.to_string()} selected={*length == self.length}>
+                                    { length_label(*length) }
+                                </option>
+                            })
+                        }
+                        </select>
+                    </div>
+                    <div class={classes!("element", if self.options_visible { None } else { Some("hidden") })}>
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::ShowAliasManager)}
+                        >
+                            { "Manage aliases" }
+                        </button>
+                    </div>
+                    <div class={classes!("element", if self.options_visible { None } else { Some("hidden") })}>
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::ShowPasswordStore)}
+                        >
+                            { "Store a password" }
+                        </button>
+                    </div>
+                    <div class={classes!("element", if self.options_visible { None } else { Some("hidden") })}>
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::ShowMasterPasswordChange)}
+                        >
+                            { "Change master password" }
+                        </button>
+                    </div>
+                    <div class={classes!("element", if self.options_visible { None } else { Some("hidden") })}>
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::ShowBackup)}
+                        >
+                            { "Export/import aliases" }
+                        </button>
+                    </div>
+                    <div class={classes!("element", if self.options_visible { None } else { Some("hidden") })}>
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::ShowSettings)}
+                        >
+                            { "Settings" }
+                        </button>
+                    </div>
+                    <div class="element">
+                        <button type="button"
+                            title="Ctrl+Shift+L"
+                            onclick={ctx.link().callback(|_| Msg::Lock)}
+                        >
+                            { "Lock now" }
+                        </button>
+                    </div>
+                </>
+            }
+        };
+
+        let backup_view = if let Some(preview) = &self.import_preview {
+            html! {
+                <ImportReview
+                    preview={preview.clone()}
+                    on_apply={ctx.link().callback(Msg::ApplyImport)}
+                    on_cancel={ctx.link().callback(|_| Msg::CancelImport)}
+                />
+            }
+        } else {
+            let class = if self.backup_msg.is_some() { None } else { Some("invisible") };
+            html! {
+                <>
+                    if self.backup_in_progress {
+                        <div class="overlay">
+                            <div class="spinner"/>
+                            <div>{ "Please wait..." }</div>
+                        </div>
+                    }
+                    <div class={classes!("element", class)}>
+                        { self.backup_msg.clone().unwrap_or_default() }
+                    </div>
+                    <SecretInput
+                        text={self.backup_password.clone()}
+                        focus=true
+                        id="backup-pass-input"
+                        hint="Enter backup password..."
+                        keyboard={keyboard_use}
+                        on_input={ctx.link().callback(Msg::OnBackupPasswordInput)}
+                        on_focus={ctx.link().callback(Msg::OnInputFocus)}
+                        on_enter={ctx.link().callback(|_| Msg::OnEnterPressed)}
+                    />
+                    <SecretInput
+                        text={self.backup_password2.clone()}
+                        disabled={self.backup_password.is_empty()}
+                        id="backup-pass2-input"
+                        hint="Repeat backup password..."
+                        keyboard={keyboard_use}
+                        on_input={ctx.link().callback(Msg::OnBackupPassword2Input)}
+                        on_focus={ctx.link().callback(Msg::OnInputFocus)}
+                        on_enter={ctx.link().callback(|_| Msg::OnEnterPressed)}
+                    />
+                    <div class="element">
+                        { "Backup is encrypted with master password unless you set a separate password. \
+                            To import a backup enter the password it was encrypted with." }
+                    </div>
+                    <div class="element">
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::Export)}
+                            disabled={!self.backup_password_looks_valid() || self.backup_in_progress}
+                        >
+                            { "Export" }
+                        </button>
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::Import)}
+                            disabled={self.backup_password.is_empty() || self.backup_in_progress}
+                        >
+                            { "Import" }
+                        </button>
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::CloseBackup)}
+                            disabled={self.backup_in_progress}
+                        >
+                            { "Back" }
+                        </button>
+                    </div>
+                </>
+            }
+        };
+
+        let master_password_change_view = if let Some(changes) = &self.password_changes {
+            html! {
+                <PasswordChanges
+                    changes={changes.clone()}
+                    in_progress={self.mp_change_in_progress}
+                    on_confirm={ctx.link().callback(|_| Msg::ConfirmMasterPasswordChange)}
+                    on_cancel={ctx.link().callback(|_| Msg::CloseMasterPasswordChange)}
+                />
+            }
+        } else {
+            let class = if self.mp_wrong { None } else { Some("invisible") };
+            html! {
+                <>
+                    if self.mp_change_in_progress {
+                        <div class="overlay">
+                            <div class="spinner"/>
+                            <div>{ "Deriving new passwords..." }</div>
+                        </div>
+                    }
+                    <div class={classes!("element", class)}>{ "Wrong master password" }</div>
+                    <SecretInput
+                        text={self.old_master_password.clone()}
+                        focus=true
+                        id="old-mp-input"
+                        hint="Enter current master password..."
+                        keyboard={keyboard_use}
+                        on_input={ctx.link().callback(Msg::OnOldPasswordInput)}
+                        on_focus={ctx.link().callback(Msg::OnInputFocus)}
+                        on_enter={ctx.link().callback(|_| Msg::OnEnterPressed)}
+                    />
+                    <SecretInput
+                        text={self.new_master_password.clone()}
+                        id="new-mp-input"
+                        hint="Enter new master password..."
+                        keyboard={keyboard_use}
+                        on_input={ctx.link().callback(Msg::OnNewPasswordInput)}
+                        on_focus={ctx.link().callback(Msg::OnInputFocus)}
+                        on_enter={ctx.link().callback(|_| Msg::OnEnterPressed)}
+                    />
+                    <SecretInput
+                        text={self.new_master_password2.clone()}
+                        id="new-mp2-input"
+                        hint="Repeat new master password..."
+                        keyboard={keyboard_use}
+                        on_input={ctx.link().callback(Msg::OnNewPassword2Input)}
+                        on_focus={ctx.link().callback(Msg::OnInputFocus)}
+                        on_enter={ctx.link().callback(|_| Msg::OnEnterPressed)}
+                    />
+                    <div class="element">
+                        { "Passwords derived for every alias will change" }
+                    </div>
+                    <div class="element">
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::PreviewMasterPasswordChange)}
+                            disabled={!self.new_mp_looks_valid() || self.mp_change_in_progress}
+                        >
+                            { "Preview changes" }
+                        </button>
+                        <button type="button"
+                            onclick={ctx.link().callback(|_| Msg::CloseMasterPasswordChange)}
+                            disabled={self.mp_change_in_progress}
+                        >
+                            { "Cancel" }
+                        </button>
+                    </div>
                 </>
             }
         };
@@ -659,16 +2053,45 @@ impl Component for App {
                     </>
                 },
                 AppState::Initialized => main_view,
+                AppState::ChangingMasterPassword => master_password_change_view,
+                AppState::ManagingAliases => html! {
+                    <AliasManager
+                        aliases={self.known_aliases.clone()}
+                        on_update={ctx.link().callback(Msg::UpdateAlias)}
+                        on_remove={ctx.link().callback(Msg::RemoveAliases)}
+                        on_close={ctx.link().callback(|_| Msg::CloseAliasManager)}
+                    />
+                },
+                AppState::StoringPassword => html! {
+                    <PasswordStore
+                        alias={self.alias.trim().to_string()}
+                        aliases={self.known_aliases.clone()}
+                        on_save={ctx.link().callback(Msg::StorePassword)}
+                        on_cancel={ctx.link().callback(|_| Msg::ClosePasswordStore)}
+                    />
+                },
+                AppState::Backup => backup_view,
+                AppState::Settings => html! {
+                    <SettingsEditor
+                        settings={self.settings.clone()}
+                        on_save={ctx.link().callback(Msg::SaveSettings)}
+                        on_close={ctx.link().callback(|_| Msg::CloseSettings)}
+                    />
+                },
+                AppState::ResettingDb => html! {
+                    <DbReset
+                        damaged={matches!(self.unlock_error, Some(UnlockError::CorruptDb(_)))}
+                        restore_backup={self.restore_backup}
+                        alias_names={self.recovery_alias_names.clone()}
+                        message={self.recovery_msg.clone()}
+                        on_save_names={ctx.link().callback(|_| Msg::SaveAliasNames)}
+                        on_reset={ctx.link().callback(|_| Msg::ResetDb)}
+                        on_cancel={ctx.link().callback(|_| Msg::CloseDbReset)}
+                    />
+                },
             }}
             { maybe_keyboard }
             </main>
         }
     }
-
-    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
-        if self.state == AppState::Unlocking {
-            let callback = ctx.link().callback(Msg::Initialize);
-            self.initialize_psh(callback);
-        }
-    }
 }
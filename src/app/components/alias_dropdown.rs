@@ -1,10 +1,14 @@
 use yew::prelude::*;
 
+use crate::fuzzy::Match;
+
 #[derive(Properties, PartialEq)]
 pub struct AliasDropdownProps {
     pub show: bool,
     pub selected: Option<usize>,
-    pub matched_aliases: Vec<String>,
+    // Aliases that match input ordered from the best match
+    pub matches: Vec<Match>,
+    pub stored_aliases: Vec<String>,
     pub on_click: Callback<String>,
     pub on_hover: Callback<Option<usize>>
 }
@@ -29,15 +33,6 @@ pub fn alias_dropdown(props: &AliasDropdownProps) -> Html {
         )
     }
 
-    let on_click = {
-        let on_click = props.on_click.clone();
-        Callback::from(move |e: MouseEvent| {
-            let el = e.target_dyn_into::<web_sys::HtmlDivElement>().unwrap();
-            let alias = el.inner_html();
-            on_click.emit(alias);
-        })
-    };
-
     let on_mouseleave = {
         let on_hover = props.on_hover.clone();
         Callback::from(move |_| {
@@ -45,22 +40,11 @@ pub fn alias_dropdown(props: &AliasDropdownProps) -> Html {
         })
     };
 
-    let on_mouseover = {
-        let on_hover = props.on_hover.clone();
-        Callback::from(move |e: MouseEvent| {
-            let el = e.target_dyn_into::<web_sys::HtmlDivElement>().unwrap();
-            let alias_idx: usize = el.id()
-                .rsplit_once("-").unwrap()
-                .1.parse().unwrap();
-            on_hover.emit(Some(alias_idx));
-        })
-    };
-
     html! {
         <div
             class={classes!(
                 "dropdown",
-                if !props.show || props.matched_aliases.len() == 0 {
+                if !props.show || props.matches.is_empty() {
                     Some("invisible")
                 } else {
                     None
@@ -69,22 +53,38 @@ pub fn alias_dropdown(props: &AliasDropdownProps) -> Html {
             onmouseleave={on_mouseleave.clone()}
         >
             {
-                (*props.matched_aliases).iter().enumerate().map(|(idx, alias)| {
+                props.matches.iter().enumerate().map(|(idx, m)| {
+                    let alias = &m.text;
                     let maybe_selected =
                         if props.selected.is_some() && idx == props.selected.unwrap() {
                             Some("selected")
                         } else {
                             None
                         };
+                    // Marker is drawn with CSS
+                    let maybe_stored = if props.stored_aliases.contains(alias) {
+                        Some("stored")
+                    } else {
+                        None
+                    };
+                    let on_click = {
+                        let on_click = props.on_click.clone();
+                        let alias = alias.clone();
+                        Callback::from(move |_| on_click.emit(alias.clone()))
+                    };
+                    let on_mouseover = {
+                        let on_hover = props.on_hover.clone();
+                        Callback::from(move |_| on_hover.emit(Some(idx)))
+                    };
                     html!{
                         <div
-                            class={classes!("variant", maybe_selected)}
+                            class={classes!("variant", maybe_selected, maybe_stored)}
                             key={alias.clone()}
                             id={format!("alias-{}", idx)}
-                            onclick={on_click.clone()}
-                            onmouseover={on_mouseover.clone()}
+                            onclick={on_click}
+                            onmouseover={on_mouseover}
                         >
-                            {alias.clone()}
+                            { highlight(m) }
                         </div>
                     }
                 }).collect::<Html>()
@@ -92,3 +92,22 @@ pub fn alias_dropdown(props: &AliasDropdownProps) -> Html {
         </div>
     }
 }
+
+// Alias with matched characters marked
+fn highlight(m: &Match) -> Html {
+    let mut runs: Vec<(bool, String)> = Vec::new();
+    for (i, c) in m.text.chars().enumerate() {
+        let matched = m.positions.contains(&i);
+        match runs.last_mut() {
+            Some((run_matched, run)) if *run_matched == matched => run.push(c),
+            _ => runs.push((matched, c.to_string())),
+        }
+    }
+    runs.into_iter().map(|(matched, run)| {
+        if matched {
+            html! { <mark>{ run }</mark> }
+        } else {
+            html! { { run } }
+        }
+    }).collect::<Html>()
+}
@@ -1,11 +1,16 @@
 use yew::prelude::*;
 
+use crate::domain::{registrable_domain, Sites};
+use crate::fuzzy;
+
 use super::alias_dropdown::AliasDropdown;
 
 #[derive(Properties, PartialEq)]
 pub struct AliasInputProps {
     pub text: String,
     pub known_aliases: Vec<String>,
+    // Known aliases that hold stored passwords
+    pub stored_aliases: Vec<String>,
     pub keyboard: bool,
     pub on_input: Callback<(String, bool)>,
     pub on_focus: Callback<NodeRef>,
@@ -19,15 +24,10 @@ pub fn alias_input(props: &AliasInputProps) -> Html {
     let dropdown_closed_on_select = use_state(|| false);
     let dropdown_selected_idx = use_state(|| None::<usize>);
     let alias_matches = use_memo(
-        |(aliases, string)| {
-            let matches: Vec<String> = aliases.iter()
-                .filter(|a| a.contains(string))
-                .map(|a| a.clone())
-                .collect();
-            matches
-        },
+        |(aliases, string)| fuzzy::rank(string, aliases),
         (props.known_aliases.clone(), props.text.clone())
     );
+    let sites = use_memo(|aliases| Sites::new(aliases), props.known_aliases.clone());
     let dropdown_last_idx = use_memo(
         |matches| {
             if matches.len() > 0 { Some(matches.len() - 1) }
@@ -86,6 +86,36 @@ pub fn alias_input(props: &AliasInputProps) -> Html {
         })
     };
 
+    let text = props.text.trim();
+    // Domain offered instead of URL or host name that was input
+    let normalized = registrable_domain(text).filter(|domain| *domain != text.to_lowercase());
+    // Existing alias of the same site as the new one
+    let same_site_alias = if props.known_aliases.iter().any(|a| a == text) {
+        None
+    } else {
+        sites.same_site(text).map(str::to_string)
+    };
+
+    let on_normalize = {
+        let check_alias = check_alias.clone();
+        let normalized = normalized.clone();
+        Callback::from(move |_| {
+            if let Some(domain) = &normalized {
+                check_alias.emit(domain.clone());
+            }
+        })
+    };
+
+    let on_use_existing = {
+        let check_alias = check_alias.clone();
+        let same_site_alias = same_site_alias.clone();
+        Callback::from(move |_| {
+            if let Some(alias) = &same_site_alias {
+                check_alias.emit(alias.clone());
+            }
+        })
+    };
+
     let on_focus = {
         let focused = focused.clone();
         let show_dropdown = show_dropdown.clone();
@@ -118,7 +148,7 @@ pub fn alias_input(props: &AliasInputProps) -> Html {
         Callback::from(move |e: KeyboardEvent| {
             if e.key() == "Enter" {
                 if let Some(selected_idx) = *dropdown_selected_idx {
-                    let alias = alias_matches.get(selected_idx).unwrap();
+                    let alias = &alias_matches.get(selected_idx).unwrap().text;
                     show_dropdown.set(false);
                     dropdown_closed_on_select.set(true);
                     dropdown_selected_idx.set(None);
@@ -188,25 +218,40 @@ pub fn alias_input(props: &AliasInputProps) -> Html {
     };
 
     html! {
-        <div class="element">
-            <input type="text"
-                id="alias-input"
-                value={props.text.clone()}
-                oninput={on_input}
-                onfocus={on_focus}
-                onblur={on_blur}
-                onkeydown={on_key_down}
-                ref={input_ref}
-                placeholder="Enter alias..."
-                inputmode={if props.keyboard { "none" } else { "text" }}
-            />
-            <AliasDropdown
-                show={*show_dropdown}
-                selected={*dropdown_selected_idx}
-                matched_aliases={(*alias_matches).clone()}
-                on_click={on_match_click}
-                on_hover={on_match_hover}
-            />
-        </div>
+        <>
+            <div class="element">
+                <input type="text"
+                    id="alias-input"
+                    value={props.text.clone()}
+                    oninput={on_input}
+                    onfocus={on_focus}
+                    onblur={on_blur}
+                    onkeydown={on_key_down}
+                    ref={input_ref}
+                    placeholder="Enter alias..."
+                    inputmode={if props.keyboard { "none" } else { "text" }}
+                />
+                <AliasDropdown
+                    show={*show_dropdown}
+                    selected={*dropdown_selected_idx}
+                    matches={(*alias_matches).clone()}
+                    stored_aliases={props.stored_aliases.clone()}
+                    on_click={on_match_click}
+                    on_hover={on_match_hover}
+                />
+            </div>
+            if let Some(domain) = normalized {
+                <div class="element alias-hint">
+                    <span>{ format!("Looks like a link to {}", domain) }</span>
+                    <button type="button" class="small" onclick={on_normalize}>{ "Use domain" }</button>
+                </div>
+            }
+            if let Some(alias) = same_site_alias {
+                <div class="element alias-hint">
+                    <span>{ format!("Alias \"{}\" already exists for this site", alias) }</span>
+                    <button type="button" class="small" onclick={on_use_existing}>{ "Use it" }</button>
+                </div>
+            }
+        </>
     }
 }
@@ -0,0 +1,455 @@
+use std::collections::HashSet;
+
+use yew::prelude::*;
+
+use crate::backend::{AliasInfo, Passphrase, Username};
+
+pub const CHARSET_LABELS: [&str; 3] = ["Standard", "Require All", "Reduced"];
+/// Lengths of derived passwords user can choose from (`None` is full length).
+pub const LENGTHS: [Option<usize>; 8] =
+    [None, Some(8), Some(10), Some(12), Some(14), Some(16), Some(20), Some(24)];
+
+/// Numbers of passphrase words user can choose from (`None` is password of characters).
+pub const WORD_COUNTS: [Option<usize>; 6] = [None, Some(4), Some(5), Some(6), Some(7), Some(8)];
+/// Passphrase word separators along with their names.
+pub const SEPARATORS: [(&str, &str); 5] =
+    [("-", "Hyphen"), (" ", "Space"), (".", "Dot"), ("_", "Underscore"), ("", "No separator")];
+
+/// Ways to keep alias username.
+pub const USERNAME_KINDS: [&str; 3] = ["No username", "Stored username", "Derived username"];
+
+/// Short description of alias settings.
+pub fn describe(info: &AliasInfo) -> String {
+    let username = match &info.username {
+        Some(Username::Plain(username)) => format!(", user {}", username),
+        Some(Username::Derived) => ", derived user".to_string(),
+        None => String::new(),
+    };
+    if info.stored {
+        return format!("Stored password{}", username);
+    }
+    let length = match &info.passphrase {
+        Some(passphrase) => format!(", {} words", passphrase.words),
+        None => info.length.map(|length| format!(", {} chars", length)).unwrap_or_default(),
+    };
+    let generation = if info.generation > 0 {
+        format!(", generation {}", info.generation + 1)
+    } else {
+        String::new()
+    };
+    let secret = if info.use_secret { ", secret" } else { "" };
+    format!("{}{}{}{}{}", CHARSET_LABELS[info.charset], length, generation, username, secret)
+}
+
+pub fn length_label(length: Option<usize>) -> String {
+    match length {
+        Some(length) => format!("{} chars", length),
+        None => "Full length".to_string(),
+    }
+}
+
+pub fn word_count_label(words: Option<usize>) -> String {
+    match words {
+        Some(words) => format!("Passphrase of {} words", words),
+        None => "Password".to_string(),
+    }
+}
+
+/// Passphrase of given number of words that keeps separator of `current` one.
+pub fn with_word_count(current: Option<&Passphrase>, words: Option<usize>) -> Option<Passphrase> {
+    let separator = current.map_or(SEPARATORS[0].0, |p| p.separator.as_str()).to_string();
+    words.map(|words| Passphrase { words, separator })
+}
+
+#[derive(Properties, PartialEq)]
+pub struct AliasManagerProps {
+    pub aliases: Vec<AliasInfo>,
+    pub on_update: Callback<(String, AliasInfo)>,
+    pub on_remove: Callback<Vec<String>>,
+    pub on_close: Callback<()>,
+}
+
+#[function_component(AliasManager)]
+pub fn alias_manager(props: &AliasManagerProps) -> Html {
+    let filter = use_state(String::new);
+    let selected = use_state(HashSet::<String>::new);
+    let editing = use_state(|| None::<String>);
+    let confirm_removal = use_state(|| false);
+    let filter_ref = use_node_ref();
+
+    { // Forget selected aliases that don't exist anymore
+        let selected = selected.clone();
+        use_effect_with_deps(
+            move |aliases: &Vec<AliasInfo>| {
+                let existing: HashSet<String> = selected.iter()
+                    .filter(|s| aliases.iter().any(|a| a.alias == **s))
+                    .cloned()
+                    .collect();
+                if existing.len() != selected.len() {
+                    selected.set(existing);
+                }
+            },
+            props.aliases.clone(),
+        );
+    }
+
+    let visible_aliases: Vec<&AliasInfo> = props.aliases.iter()
+        .filter(|a| a.alias.contains(filter.as_str()))
+        .collect();
+
+    let on_filter_input = {
+        let filter = filter.clone();
+        let filter_ref = filter_ref.clone();
+        Callback::from(move |_| {
+            let input = filter_ref.cast::<web_sys::HtmlInputElement>().unwrap();
+            filter.set(input.value());
+        })
+    };
+
+    let on_select_all = {
+        let selected = selected.clone();
+        let visible: HashSet<String> = visible_aliases.iter()
+            .map(|a| a.alias.clone())
+            .collect();
+        Callback::from(move |_| {
+            if visible.is_subset(&selected) {
+                selected.set(selected.difference(&visible).cloned().collect());
+            } else {
+                selected.set(selected.union(&visible).cloned().collect());
+            }
+        })
+    };
+
+    let on_remove_click = {
+        let confirm_removal = confirm_removal.clone();
+        Callback::from(move |_| confirm_removal.set(true))
+    };
+
+    let on_remove_confirm = {
+        let selected = selected.clone();
+        let confirm_removal = confirm_removal.clone();
+        let on_remove = props.on_remove.clone();
+        Callback::from(move |_| {
+            on_remove.emit(selected.iter().cloned().collect());
+            selected.set(HashSet::new());
+            confirm_removal.set(false);
+        })
+    };
+
+    let on_remove_cancel = {
+        let confirm_removal = confirm_removal.clone();
+        Callback::from(move |_| confirm_removal.set(false))
+    };
+
+    let on_edit_done = {
+        let editing = editing.clone();
+        let on_update = props.on_update.clone();
+        Callback::from(move |maybe_update: Option<(String, AliasInfo)>| {
+            if let Some(update) = maybe_update {
+                on_update.emit(update);
+            }
+            editing.set(None);
+        })
+    };
+
+    let on_close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_| on_close.emit(()))
+    };
+
+    let rows = visible_aliases.iter().map(|info| {
+        if editing.as_ref() == Some(&info.alias) {
+            let other_aliases: Vec<String> = props.aliases.iter()
+                .filter(|a| a.alias != info.alias)
+                .map(|a| a.alias.clone())
+                .collect();
+            return html! {
+                <AliasEditor
+                    key={info.alias.clone()}
+                    info={(*info).clone()}
+                    other_aliases={other_aliases}
+                    on_done={on_edit_done.clone()}
+                />
+            };
+        }
+
+        let on_toggle = {
+            let selected = selected.clone();
+            let alias = info.alias.clone();
+            Callback::from(move |_| {
+                let mut new_selected = (*selected).clone();
+                if !new_selected.remove(&alias) {
+                    new_selected.insert(alias.clone());
+                }
+                selected.set(new_selected);
+            })
+        };
+        let on_edit = {
+            let editing = editing.clone();
+            let alias = info.alias.clone();
+            Callback::from(move |_| editing.set(Some(alias.clone())))
+        };
+
+        html! {
+            <div class="alias-row" key={info.alias.clone()}>
+                <input type="checkbox"
+                    checked={selected.contains(&info.alias)}
+                    onclick={on_toggle}
+                />
+                <div class="alias-name">{ &info.alias }</div>
+                <div class="alias-settings">{ describe(info) }</div>
+                <button type="button" class="small" onclick={on_edit}>{ "Edit" }</button>
+            </div>
+        }
+    }).collect::<Html>();
+
+    html! {
+        <>
+            <div class="element">
+                <input type="text"
+                    ref={filter_ref}
+                    value={(*filter).clone()}
+                    oninput={on_filter_input}
+                    placeholder="Filter aliases..."
+                />
+            </div>
+            <div class="element alias-list">
+                if props.aliases.is_empty() {
+                    <div>{ "No stored aliases" }</div>
+                }
+                { rows }
+            </div>
+            if *confirm_removal {
+                <div class="element">
+                    { format!("Remove {} selected aliases? This can't be undone.", selected.len()) }
+                </div>
+                <div class="element">
+                    <button type="button" onclick={on_remove_confirm}>{ "Remove" }</button>
+                    <button type="button" onclick={on_remove_cancel}>{ "Cancel" }</button>
+                </div>
+            } else {
+                <div class="element">
+                    <button type="button"
+                        onclick={on_select_all}
+                        disabled={visible_aliases.is_empty()}
+                    >
+                        { "Select/unselect all" }
+                    </button>
+                    <button type="button"
+                        onclick={on_remove_click}
+                        disabled={selected.is_empty()}
+                    >
+                        { format!("Remove selected ({})", selected.len()) }
+                    </button>
+                </div>
+                <div class="element">
+                    <button type="button" onclick={on_close}>{ "Back" }</button>
+                </div>
+            }
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct AliasEditorProps {
+    pub info: AliasInfo,
+    pub other_aliases: Vec<String>,
+    pub on_done: Callback<Option<(String, AliasInfo)>>,
+}
+
+#[function_component(AliasEditor)]
+pub fn alias_editor(props: &AliasEditorProps) -> Html {
+    let name = use_state(|| props.info.alias.clone());
+    let username_kind = use_state(|| match props.info.username {
+        None => 0,
+        Some(Username::Plain(_)) => 1,
+        Some(Username::Derived) => 2,
+    });
+    let username = use_state(|| match &props.info.username {
+        Some(Username::Plain(username)) => username.clone(),
+        _ => String::new(),
+    });
+    let passphrase = use_state(|| props.info.passphrase.clone());
+    let name_ref = use_node_ref();
+    let charset_ref = use_node_ref();
+    let length_ref = use_node_ref();
+    let secret_ref = use_node_ref();
+
+    let new_name = name.trim().to_string();
+    let name_taken = props.other_aliases.contains(&new_name);
+    let new_username = match *username_kind {
+        1 if !username.trim().is_empty() => Some(Username::Plain(username.trim().to_string())),
+        2 => Some(Username::Derived),
+        _ => None,
+    };
+    let can_save = !new_name.is_empty() && !name_taken;
+    // Password is derived from alias name, so renaming changes it as well
+    let renamed = new_name != props.info.alias;
+
+    let on_name_input = {
+        let name = name.clone();
+        let name_ref = name_ref.clone();
+        Callback::from(move |_| {
+            let input = name_ref.cast::<web_sys::HtmlInputElement>().unwrap();
+            name.set(input.value());
+        })
+    };
+
+    let on_username_kind_change = {
+        let username_kind = username_kind.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_dyn_into::<web_sys::HtmlSelectElement>().unwrap();
+            username_kind.set(select.value().parse().unwrap());
+        })
+    };
+
+    let on_word_count_change = {
+        let passphrase = passphrase.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_dyn_into::<web_sys::HtmlSelectElement>().unwrap();
+            let words = WORD_COUNTS[select.value().parse::<usize>().unwrap()];
+            passphrase.set(with_word_count(passphrase.as_ref(), words));
+        })
+    };
+
+    let on_separator_change = {
+        let passphrase = passphrase.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_dyn_into::<web_sys::HtmlSelectElement>().unwrap();
+            let separator = SEPARATORS[select.value().parse::<usize>().unwrap()].0.to_string();
+            passphrase.set(passphrase.as_ref().map(|p| Passphrase { separator, ..p.clone() }));
+        })
+    };
+
+    let on_username_input = {
+        let username = username.clone();
+        Callback::from(move |e: InputEvent| {
+            let input = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap();
+            username.set(input.value());
+        })
+    };
+
+    let on_save = {
+        let old_info = props.info.clone();
+        let charset_ref = charset_ref.clone();
+        let length_ref = length_ref.clone();
+        let secret_ref = secret_ref.clone();
+        let passphrase = passphrase.clone();
+        let on_done = props.on_done.clone();
+        Callback::from(move |_| {
+            let info = if old_info.stored {
+                // Stored password is kept by backend
+                AliasInfo { alias: new_name.clone(), username: new_username.clone(), ..old_info.clone() }
+            } else {
+                let charset = charset_ref.cast::<web_sys::HtmlSelectElement>().unwrap();
+                let secret = secret_ref.cast::<web_sys::HtmlInputElement>().unwrap();
+                // Length select is absent in passphrase mode
+                let length = length_ref.cast::<web_sys::HtmlSelectElement>()
+                    .and_then(|length| LENGTHS[length.value().parse::<usize>().unwrap()]);
+                AliasInfo {
+                    alias: new_name.clone(),
+                    use_secret: secret.checked(),
+                    charset: charset.value().parse().unwrap(),
+                    length,
+                    passphrase: (*passphrase).clone(),
+                    username: new_username.clone(),
+                    ..old_info.clone()
+                }
+            };
+            on_done.emit(Some((old_info.alias.clone(), info)));
+        })
+    };
+
+    let on_cancel = {
+        let on_done = props.on_done.clone();
+        Callback::from(move |_| on_done.emit(None))
+    };
+
+    html! {
+        <div class="alias-row editing">
+            <input type="text"
+                ref={name_ref}
+                value={(*name).clone()}
+                oninput={on_name_input}
+            />
+            if !props.info.stored {
+                <select ref={charset_ref}>
+                {
+                    for CHARSET_LABELS.iter().enumerate().map(|(i, label)| html! {
+                        <option value={i.to_string()} selected={i == props.info.charset}>
+                            { label }
+                        </option>
+                    })
+                }
+                </select>
+                <select onchange={on_word_count_change}>
+                {
+                    for WORD_COUNTS.iter().enumerate().map(|(i, words)| html! {
+                        <option value={i.to_string()}
+                            selected={*words == passphrase.as_ref().map(|p| p.words)}
+                        >
+                            { word_count_label(*words) }
+                        </option>
+                    })
+                }
+                </select>
+                if let Some(passphrase) = passphrase.as_ref() {
+                    <select onchange={on_separator_change}>
+                    {
+                        for SEPARATORS.iter().enumerate().map(|(i, (separator, label))| html! {
+                            <option value={i.to_string()} selected={*separator == passphrase.separator}>
+                                { label }
+                            </option>
+                        })
+                    }
+                    </select>
+                } else {
+                    <select ref={length_ref}>
+                    {
+                        for LENGTHS.iter().enumerate().map(|(i, length)| html! {
+                            <option value={i.to_string()} selected={*length == props.info.length}>
+                                { length_label(*length) }
+                            </option>
+                        })
+                    }
+                    </select>
+                }
+            }
+            <select onchange={on_username_kind_change}>
+            {
+                for USERNAME_KINDS.iter().enumerate().map(|(i, label)| html! {
+                    <option value={i.to_string()} selected={i == *username_kind}>
+                        { label }
+                    </option>
+                })
+            }
+            </select>
+            if *username_kind == 1 {
+                <input type="text"
+                    placeholder="Username or email"
+                    value={(*username).clone()}
+                    oninput={on_username_input}
+                    autocomplete="off"
+                />
+            }
+            if !props.info.stored {
+                <label>
+                    <input type="checkbox" ref={secret_ref} checked={props.info.use_secret}/>
+                    { "Secret" }
+                </label>
+            }
+            if name_taken {
+                <div>{ "Alias already exists" }</div>
+            }
+            if !props.info.stored && renamed {
+                <div>{ "Warning: renaming alias changes its derived password" }</div>
+            } else if !props.info.stored {
+                <div>{ "Note: changing alias name or settings changes derived password" }</div>
+            }
+            <button type="button" class="small" onclick={on_save} disabled={!can_save}>
+                { "Save" }
+            </button>
+            <button type="button" class="small" onclick={on_cancel}>{ "Cancel" }</button>
+        </div>
+    }
+}
@@ -14,14 +14,12 @@ pub fn collapsible(props: &CollapsibleProps) -> Html {
 
     {
         let open = open.clone();
-        let start_collapsed = props.start_collapsed.clone();
+        // Follow the prop if parent changes it
         use_effect_with_deps(
-            move |_| {
-                if start_collapsed {
-                    open.set(false);
-                }
+            move |start_collapsed| {
+                open.set(!*start_collapsed);
             },
-            (),
+            props.start_collapsed,
         );
     }
 
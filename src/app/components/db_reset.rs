@@ -0,0 +1,103 @@
+use yew::prelude::*;
+
+// Phrase user has to type to confirm database reset
+const CONFIRMATION_PHRASE: &str = "delete my aliases";
+
+#[derive(Properties, PartialEq)]
+pub struct DbResetProps {
+    // Whether database is reset because it's damaged (rather than master password forgotten)
+    pub damaged: bool,
+    // Whether backup is going to be restored after reset
+    pub restore_backup: bool,
+    // Alias names kept unencrypted (if user opted in)
+    pub alias_names: Vec<String>,
+    // Outcome of alias names saving
+    pub message: Option<String>,
+    pub on_save_names: Callback<()>,
+    pub on_reset: Callback<()>,
+    pub on_cancel: Callback<()>,
+}
+
+/// Reset of psh database for the case when master password is forgotten
+/// or database is damaged.
+#[function_component(DbReset)]
+pub fn db_reset(props: &DbResetProps) -> Html {
+    let phrase = use_state(String::new);
+
+    let on_phrase_input = {
+        let phrase = phrase.clone();
+        Callback::from(move |e: InputEvent| {
+            let input = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap();
+            phrase.set(input.value());
+        })
+    };
+
+    let on_save_names = {
+        let on_save_names = props.on_save_names.clone();
+        Callback::from(move |_| on_save_names.emit(()))
+    };
+
+    let on_reset = {
+        let on_reset = props.on_reset.clone();
+        Callback::from(move |_| on_reset.emit(()))
+    };
+
+    let on_cancel = {
+        let on_cancel = props.on_cancel.clone();
+        Callback::from(move |_| on_cancel.emit(()))
+    };
+
+    let confirmed = phrase.trim().eq_ignore_ascii_case(CONFIRMATION_PHRASE);
+
+    html! {
+        <>
+            <div class="element">
+            if props.damaged {
+                { "Psh database is damaged and can't be unlocked. Resetting deletes it \
+                    from this device: all stored aliases and their settings will be lost." }
+            } else {
+                { "Forgotten master password can't be recovered. Resetting deletes Psh database \
+                    from this device: all stored aliases and their settings will be lost. \
+                    Passwords derived with a new master password will differ from the current ones, \
+                    so you'll have to change them on every site." }
+            }
+            </div>
+            if props.restore_backup {
+                <div class="element">
+                    { "Set a new master password afterwards and import your backup file." }
+                </div>
+            }
+            if props.alias_names.is_empty() {
+                <div class="element">
+                    { "Alias names are encrypted with master password and can't be saved." }
+                </div>
+            } else {
+                <div class="element">
+                    { format!("{} alias names were kept unencrypted. Save them to know \
+                        which passwords to change.", props.alias_names.len()) }
+                </div>
+                <div class="element">
+                    <button type="button" onclick={on_save_names}>{ "Save alias names" }</button>
+                </div>
+            }
+            if let Some(message) = &props.message {
+                <div class="element">{ message }</div>
+            }
+            <div class="element">
+                <input type="text"
+                    id="reset-phrase-input"
+                    placeholder={format!("Type \"{}\" to confirm", CONFIRMATION_PHRASE)}
+                    value={(*phrase).clone()}
+                    oninput={on_phrase_input}
+                    autocomplete="off"
+                />
+            </div>
+            <div class="element">
+                <button type="button" onclick={on_reset} disabled={!confirmed}>
+                    { "Delete database" }
+                </button>
+                <button type="button" onclick={on_cancel}>{ "Back" }</button>
+            </div>
+        </>
+    }
+}
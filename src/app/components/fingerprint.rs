@@ -0,0 +1,56 @@
+use gloo_timers::callback::Timeout;
+use yew::prelude::*;
+
+use crate::fingerprint::{fingerprint, Glyph, COLORS, SHAPES};
+use crate::secret::SecretString;
+
+// Pause in typing after which fingerprint is shown (ms). Fingerprints of every
+// prefix would add up to reveal the whole password.
+const DEBOUNCE_MS: u32 = 1_000;
+
+#[derive(Properties, PartialEq)]
+pub struct FingerprintProps {
+    pub text: SecretString,
+    // Device fingerprint key (fingerprint isn't shown until it's loaded)
+    pub hash_key: Option<u64>,
+}
+
+/// Row of colored glyphs derived from (master) password being typed, so that
+/// user can spot a typo before waiting for unlock to fail. It's shown only
+/// once user stops typing.
+#[function_component(Fingerprint)]
+pub fn fingerprint_view(props: &FingerprintProps) -> Html {
+    let glyphs = use_state(Vec::<Glyph>::new);
+
+    {
+        let glyphs = glyphs.clone();
+        use_effect_with_deps(
+            move |(text, hash_key)| {
+                glyphs.set(Vec::new());
+                let timeout = match *hash_key {
+                    Some(key) if !text.is_empty() => {
+                        let text = text.clone();
+                        Some(Timeout::new(DEBOUNCE_MS, move || {
+                            glyphs.set(fingerprint(key, &text).to_vec());
+                        }))
+                    }
+                    _ => None,
+                };
+                move || drop(timeout)
+            },
+            (props.text.clone(), props.hash_key),
+        );
+    }
+
+    html! {
+        <div class="element fingerprint" title="Fingerprint of master password">
+        {
+            for glyphs.iter().map(|glyph| html! {
+                <span style={format!("color: {}", COLORS[glyph.color])}>
+                    { SHAPES[glyph.shape] }
+                </span>
+            })
+        }
+        </div>
+    }
+}
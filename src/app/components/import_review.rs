@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use yew::prelude::*;
+
+use crate::backend::{AliasInfo, ImportPreview};
+use super::alias_manager::describe;
+
+#[derive(Properties, PartialEq)]
+pub struct ImportReviewProps {
+    pub preview: ImportPreview,
+    // New aliases and aliases that replace existing ones
+    pub on_apply: Callback<(Vec<AliasInfo>, Vec<AliasInfo>)>,
+    pub on_cancel: Callback<()>,
+}
+
+#[function_component(ImportReview)]
+pub fn import_review(props: &ImportReviewProps) -> Html {
+    // Conflicting aliases to be replaced with imported ones
+    let replaced = use_state(HashSet::<String>::new);
+
+    let nothing_to_import = props.preview.new.is_empty() && replaced.is_empty();
+
+    let on_apply = {
+        let replaced = replaced.clone();
+        let new = props.preview.new.clone();
+        let replace: Vec<AliasInfo> = props.preview.conflicts.iter()
+            .filter(|c| replaced.contains(&c.imported.alias))
+            .map(|c| c.imported.clone())
+            .collect();
+        let on_apply = props.on_apply.clone();
+        Callback::from(move |_| {
+            on_apply.emit((new.clone(), replace.clone()));
+            replaced.set(HashSet::new());
+        })
+    };
+
+    let on_cancel = {
+        let on_cancel = props.on_cancel.clone();
+        Callback::from(move |_| on_cancel.emit(()))
+    };
+
+    let conflicts = props.preview.conflicts.iter().map(|conflict| {
+        let alias = conflict.imported.alias.clone();
+        let on_toggle = {
+            let replaced = replaced.clone();
+            let alias = alias.clone();
+            Callback::from(move |_| {
+                let mut new_replaced = (*replaced).clone();
+                if !new_replaced.remove(&alias) {
+                    new_replaced.insert(alias.clone());
+                }
+                replaced.set(new_replaced);
+            })
+        };
+
+        html! {
+            <div class="alias-row" key={alias.clone()}>
+                <div class="alias-name">{ &alias }</div>
+                <div class="alias-settings">
+                    { format!("{} → {}", describe(&conflict.current), describe(&conflict.imported)) }
+                </div>
+                <label>
+                    <input type="checkbox" checked={replaced.contains(&alias)} onclick={on_toggle}/>
+                    { "Use imported" }
+                </label>
+            </div>
+        }
+    }).collect::<Html>();
+
+    html! {
+        <>
+            <div class="element">
+                { format!("New aliases: {}", props.preview.new.len()) }
+            </div>
+            <div class="element alias-list">
+            {
+                props.preview.new.iter().map(|info| html! {
+                    <div class="alias-row" key={info.alias.clone()}>
+                        <div class="alias-name">{ &info.alias }</div>
+                        <div class="alias-settings">{ describe(info) }</div>
+                    </div>
+                }).collect::<Html>()
+            }
+            </div>
+            if !props.preview.conflicts.is_empty() {
+                <div class="element">
+                    { "These aliases are stored with different settings. \
+                        Current settings are kept unless you choose otherwise:" }
+                </div>
+                <div class="element alias-list">
+                    { conflicts }
+                </div>
+            }
+            <div class="element">
+                <button type="button" onclick={on_apply} disabled={nothing_to_import}>
+                    { "Import" }
+                </button>
+                <button type="button" onclick={on_cancel}>{ "Cancel" }</button>
+            </div>
+        </>
+    }
+}
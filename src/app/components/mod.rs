@@ -1,6 +1,13 @@
 pub mod alias_input;
 pub mod alias_dropdown;
+pub mod alias_manager;
+pub mod db_reset;
+pub mod fingerprint;
+pub mod import_review;
+pub mod password_changes;
+pub mod password_store;
 pub mod secret_input;
+pub mod settings_editor;
 pub mod triswitch;
 pub mod collapsible;
 #[cfg(feature = "keyboard")]
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use yew::prelude::*;
+
+use crate::backend::{PasswordChange, Secret};
+use crate::secret::mask;
+
+#[derive(Properties, PartialEq)]
+pub struct PasswordChangesProps {
+    pub changes: Vec<PasswordChange>,
+    // Whether the change is being saved
+    pub in_progress: bool,
+    pub on_confirm: Callback<()>,
+    pub on_cancel: Callback<()>,
+}
+
+/// Passwords that change along with master password. Master password isn't changed
+/// until user confirms, so old passwords stay available while sites get updated.
+#[function_component(PasswordChanges)]
+pub fn password_changes(props: &PasswordChangesProps) -> Html {
+    // Aliases which passwords are shown
+    let revealed = use_state(HashSet::<String>::new);
+
+    let on_confirm = {
+        let on_confirm = props.on_confirm.clone();
+        Callback::from(move |_| on_confirm.emit(()))
+    };
+
+    let on_cancel = {
+        let on_cancel = props.on_cancel.clone();
+        Callback::from(move |_| on_cancel.emit(()))
+    };
+
+    let rows = props.changes.iter().map(|change| {
+        let shown = revealed.contains(&change.alias);
+        let on_reveal = {
+            let revealed = revealed.clone();
+            let alias = change.alias.clone();
+            Callback::from(move |_| {
+                let mut new_revealed = (*revealed).clone();
+                if !new_revealed.remove(&alias) {
+                    new_revealed.insert(alias.clone());
+                }
+                revealed.set(new_revealed);
+            })
+        };
+        let password = |password: &Option<Secret>| match password {
+            Some(password) if shown => password.to_string(),
+            Some(password) => mask(password),
+            None => "(uses secret)".to_string(),
+        };
+
+        html! {
+            <tr key={change.alias.clone()}>
+                <td>{ &change.alias }</td>
+                <td>{ password(&change.old_password) }</td>
+                <td>{ password(&change.new_password) }</td>
+                <td>
+                    if change.old_password.is_some() {
+                        <button type="button" class="small" onclick={on_reveal}>
+                            { if shown { "Hide" } else { "Show" } }
+                        </button>
+                    }
+                </td>
+            </tr>
+        }
+    }).collect::<Html>();
+
+    html! {
+        <>
+            if props.in_progress {
+                <div class="overlay">
+                    <div class="spinner"/>
+                    <div>{ "Changing master password..." }</div>
+                </div>
+            }
+            <div class="element">
+                { "These passwords will change along with master password. \
+                    Update them on the sites before you confirm the change:" }
+            </div>
+            <table class="element password-changes">
+                <tr>
+                    <th>{ "Alias" }</th>
+                    <th>{ "Old password" }</th>
+                    <th>{ "New password" }</th>
+                    <th></th>
+                </tr>
+                { rows }
+            </table>
+            <div class="element">
+                <button type="button" onclick={on_confirm} disabled={props.in_progress}>
+                    { "Confirm change" }
+                </button>
+                <button type="button" onclick={on_cancel} disabled={props.in_progress}>
+                    { "Cancel" }
+                </button>
+            </div>
+        </>
+    }
+}
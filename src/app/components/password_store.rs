@@ -0,0 +1,169 @@
+use yew::prelude::*;
+
+use crate::backend::AliasInfo;
+use crate::generator::{random_password, RANDOM_LENGTHS};
+use crate::secret::{mask, SecretEdit, SecretString};
+use super::secret_input::SecretInput;
+
+#[derive(Properties, PartialEq)]
+pub struct PasswordStoreProps {
+    // Alias to start with (e.g. the one typed in main view)
+    pub alias: String,
+    pub aliases: Vec<AliasInfo>,
+    // Emits alias and its password
+    pub on_save: Callback<(String, SecretString)>,
+    pub on_cancel: Callback<()>,
+}
+
+/// Storing of password that can't be derived (e.g. issued by site) either
+/// generated randomly or supplied by user.
+#[function_component(PasswordStore)]
+pub fn password_store(props: &PasswordStoreProps) -> Html {
+    let name = use_state(|| props.alias.clone());
+    let password = use_state(SecretString::default);
+    let revealed = use_state(|| false);
+    let length_idx = use_state(|| 1);
+    let use_symbols = use_state(|| true);
+    let generator_failed = use_state(|| false);
+
+    let new_name = name.trim().to_string();
+    let existing = props.aliases.iter().find(|a| a.alias == new_name);
+    // Alias that uses derived password can't get stored one
+    let name_taken = existing.is_some_and(|a| !a.stored);
+    let can_save = !new_name.is_empty() && !name_taken && !password.is_empty();
+
+    let on_name_input = {
+        let name = name.clone();
+        Callback::from(move |e: InputEvent| {
+            let input = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap();
+            name.set(input.value());
+        })
+    };
+
+    let on_password_input = {
+        let password = password.clone();
+        Callback::from(move |edit: SecretEdit| {
+            let mut new_password = (*password).clone();
+            new_password.edit(edit);
+            password.set(new_password);
+        })
+    };
+
+    let on_reveal = {
+        let revealed = revealed.clone();
+        Callback::from(move |_| revealed.set(!*revealed))
+    };
+
+    let on_length_change = {
+        let length_idx = length_idx.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_dyn_into::<web_sys::HtmlSelectElement>().unwrap();
+            length_idx.set(select.value().parse().unwrap());
+        })
+    };
+
+    let on_symbols_change = {
+        let use_symbols = use_symbols.clone();
+        Callback::from(move |e: Event| {
+            let input = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap();
+            use_symbols.set(input.checked());
+        })
+    };
+
+    let on_generate = {
+        let password = password.clone();
+        let length_idx = length_idx.clone();
+        let use_symbols = use_symbols.clone();
+        let generator_failed = generator_failed.clone();
+        Callback::from(move |_| {
+            match random_password(RANDOM_LENGTHS[*length_idx], *use_symbols) {
+                Some(generated) => password.set(SecretString::new(generated)),
+                None => generator_failed.set(true),
+            }
+        })
+    };
+
+    let on_save = {
+        let password = password.clone();
+        let on_save = props.on_save.clone();
+        Callback::from(move |_: ()| {
+            if can_save {
+                on_save.emit((new_name.clone(), (*password).clone()));
+            }
+        })
+    };
+
+    let on_cancel = {
+        let on_cancel = props.on_cancel.clone();
+        Callback::from(move |_| on_cancel.emit(()))
+    };
+
+    html! {
+        <>
+            <div class="element">
+                { "Passwords that can't be derived (e.g. issued by site) are stored \
+                    in Psh database encrypted with master password." }
+            </div>
+            <div class="element">
+                <input type="text"
+                    placeholder="Alias"
+                    value={(*name).clone()}
+                    oninput={on_name_input}
+                    autocomplete="off"
+                />
+            </div>
+            if name_taken {
+                <div class="element">{ "Alias already uses derived password" }</div>
+            } else if existing.is_some() {
+                <div class="element">{ "Stored password of this alias will be replaced" }</div>
+            }
+            <SecretInput
+                text={(*password).clone()}
+                id="stored-password-input"
+                hint="Password"
+                // Built-in keyboard only types into inputs of the main component
+                keyboard=false
+                on_input={on_password_input}
+                on_focus={Callback::noop()}
+                on_enter={on_save.clone()}
+            />
+            <div class="element generator">
+                <div class="password">
+                {
+                    if *revealed {
+                        password.to_string()
+                    } else {
+                        mask(&password)
+                    }
+                }
+                </div>
+                <button type="button" class="small" onclick={on_reveal}>
+                    { if *revealed { "Hide" } else { "Show" } }
+                </button>
+            </div>
+            <div class="element generator">
+                <select onchange={on_length_change}>
+                {
+                    for RANDOM_LENGTHS.iter().enumerate().map(|(i, length)| html! {
+                        <option value={i.to_string()} selected={i == *length_idx}>
+                            { format!("{} chars", length) }
+                        </option>
+                    })
+                }
+                </select>
+                <label>
+                    <input type="checkbox" checked={*use_symbols} onchange={on_symbols_change}/>
+                    { "Symbols" }
+                </label>
+                <button type="button" class="small" onclick={on_generate}>{ "Generate" }</button>
+            </div>
+            if *generator_failed {
+                <div class="element">{ "Secure random generator isn't available" }</div>
+            }
+            <div class="element">
+                <button type="button" onclick={on_save.reform(|_| ())} disabled={!can_save}>{ "Store password" }</button>
+                <button type="button" onclick={on_cancel}>{ "Cancel" }</button>
+            </div>
+        </>
+    }
+}
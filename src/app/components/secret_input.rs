@@ -1,8 +1,15 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo_events::{EventListener, EventListenerOptions};
+use wasm_bindgen::{JsCast, JsValue};
 use yew::prelude::*;
 
+use crate::secret::{mask, SecretEdit, SecretString};
+
 #[derive(Properties, PartialEq)]
 pub struct SecretInputProps {
-    pub text: String,
+    pub text: SecretString,
     #[prop_or_default]
     pub disabled: bool,
     #[prop_or_default]
@@ -10,18 +17,100 @@ pub struct SecretInputProps {
     pub id: String,
     pub hint: String,
     pub keyboard: bool,
-    pub on_input: Callback<String>,
+    pub on_input: Callback<SecretEdit>,
     pub on_focus: Callback<NodeRef>,
     pub on_enter: Callback<()>,
 }
 
+// Whether input event replaces the whole input. Mask has nothing in common with
+// the secret but its emptiness, so only such replacements can be applied. Browsers
+// may give no target ranges for text inputs, so selection is checked then.
+fn replaces_all(e: &web_sys::InputEvent, input: &web_sys::HtmlInputElement) -> bool {
+    let range = e.get_target_ranges().get(0);
+    let offset = |name: &str| js_sys::Reflect::get(&range, &JsValue::from_str(name)).ok()?.as_f64();
+    let (start, end) = match (offset("startOffset"), offset("endOffset")) {
+        (Some(start), Some(end)) => (start as u32, end as u32),
+        _ => (
+            input.selection_start().ok().flatten().unwrap_or_default(),
+            input.selection_end().ok().flatten().unwrap_or_default(),
+        ),
+    };
+    start == 0 && end == input.value().encode_utf16().count() as u32
+}
+
+// Translates input event into secret edits. Caret is pinned to the end of
+// the input, so a non-empty selection can only mean that user selected all.
+// Composed text is left to `compositionend` handler.
+fn secret_edits(e: &web_sys::InputEvent, input: &web_sys::HtmlInputElement) -> Vec<SecretEdit> {
+    if e.is_composing() {
+        return Vec::new();
+    }
+    let selected = input.selection_start().ok().flatten() != input.selection_end().ok().flatten();
+    let inserted = match e.input_type().as_str() {
+        "insertText" => e.data(),
+        "insertFromPaste" | "insertFromDrop" => e.data_transfer()
+            .and_then(|data| data.get_data("text/plain").ok())
+            .or_else(|| e.data()),
+        "insertReplacementText" => {
+            let text = e.data_transfer()
+                .and_then(|data| data.get_data("text/plain").ok())
+                .or_else(|| e.data());
+            return match text {
+                Some(text) if replaces_all(e, input) => vec![SecretEdit::Clear, SecretEdit::Insert(text.into())],
+                _ => Vec::new(),
+            };
+        }
+        input_type if input_type.starts_with("delete") => {
+            return delete_edit(input_type, selected).into_iter().collect();
+        }
+        _ => None,
+    };
+    match inserted {
+        Some(text) if !text.is_empty() => {
+            let mut edits = Vec::new();
+            if selected {
+                edits.push(SecretEdit::Clear);
+            }
+            edits.push(SecretEdit::Insert(text.into()));
+            edits
+        }
+        _ => Vec::new(),
+    }
+}
+
+// Translates deletion into secret edit. Caret is pinned to the end, so there's
+// nothing to delete forward. Words and lines of a hidden secret can't be told
+// apart, so deleting them clears the whole secret rather than some part of it.
+fn delete_edit(input_type: &str, selected: bool) -> Option<SecretEdit> {
+    match input_type {
+        _ if selected => Some(SecretEdit::Clear),
+        "deleteContentBackward" => Some(SecretEdit::DeleteBackward),
+        "deleteWordBackward" | "deleteSoftLineBackward" | "deleteHardLineBackward"
+            | "deleteEntireSoftLine" => Some(SecretEdit::Clear),
+        _ => None,
+    }
+}
+
+// Moves caret to the end of the input unless all of it is selected
+fn pin_caret(input: &web_sys::HtmlInputElement) {
+    let len = input.value().encode_utf16().count() as u32;
+    let start = input.selection_start().ok().flatten();
+    let end = input.selection_end().ok().flatten();
+    if end != Some(len) || (start != Some(len) && start != Some(0)) {
+        input.set_selection_range(len, len).ok();
+    }
+}
+
+/// Password input that never holds actual secret. User input is intercepted before
+/// it gets to the DOM and passed to the parent as [`SecretEdit`]s, while the input
+/// itself only shows a mask that has nothing in common with the secret.
 #[function_component(SecretInput)]
 pub fn secret_input(props: &SecretInputProps) -> Html {
     let input_ref = use_node_ref();
 
     {
         let input_ref = input_ref.clone();
-        let focus = props.focus.clone();
+        let focus = props.focus;
         use_effect_with_deps(
             move |_| {
                 if focus {
@@ -33,15 +122,84 @@ pub fn secret_input(props: &SecretInputProps) -> Html {
         );
     }
 
-    let on_input = {
+    { // Yew has no `onbeforeinput` so we listen for it ourselves
         let input_ref = input_ref.clone();
         let on_input = props.on_input.clone();
-        Callback::from(move |_| {
-            let input = input_ref.cast::<web_sys::HtmlInputElement>().unwrap();
-            let secret = input.value();
-            on_input.emit(secret);
-        })
-    };
+        use_effect_with_deps(
+            move |_| {
+                let input = input_ref.cast::<web_sys::HtmlInputElement>().unwrap();
+                // Mask that input had before composition started
+                let mask = Rc::new(RefCell::new(None));
+                let before_input = {
+                    let input = input.clone();
+                    let on_input = on_input.clone();
+                    EventListener::new_with_options(
+                        &input.clone(),
+                        "beforeinput",
+                        EventListenerOptions::enable_prevent_default(),
+                        move |e| {
+                            let e = e.dyn_ref::<web_sys::InputEvent>().unwrap();
+                            // Composition can't be cancelled in some browsers
+                            e.prevent_default();
+                            for edit in secret_edits(e, &input) {
+                                on_input.emit(edit);
+                            }
+                        },
+                    )
+                };
+                // Input methods (e.g. on Android) compose text right in the input and
+                // `data` of their input events holds the whole composition so far,
+                // so composed text is taken only once composition ends
+                let composition_start = {
+                    let input = input.clone();
+                    let mask = mask.clone();
+                    EventListener::new(&input.clone(), "compositionstart", move |_| {
+                        *mask.borrow_mut() = Some(input.value());
+                    })
+                };
+                let composition_end = {
+                    let input = input.clone();
+                    let mask = mask.clone();
+                    EventListener::new(&input.clone(), "compositionend", move |e| {
+                        let e = e.dyn_ref::<web_sys::CompositionEvent>().unwrap();
+                        // Composed text is wiped from the input before it's masked
+                        if let Some(mask) = mask.borrow_mut().take() {
+                            input.set_value(&mask);
+                        }
+                        if let Some(text) = e.data().filter(|text| !text.is_empty()) {
+                            on_input.emit(SecretEdit::Insert(text.into()));
+                        }
+                    })
+                };
+                let selection_change = {
+                    let input = input.clone();
+                    let document = web_sys::window().unwrap().document().unwrap();
+                    EventListener::new(&document.clone(), "selectionchange", move |_| {
+                        let focused = document.active_element()
+                            .is_some_and(|element| element.is_same_node(Some(input.as_ref())));
+                        let composing = mask.borrow().is_some();
+                        if focused && !composing {
+                            pin_caret(&input);
+                        }
+                    })
+                };
+                move || drop((before_input, composition_start, composition_end, selection_change))
+            },
+            (),
+        );
+    }
+
+    {
+        let input_ref = input_ref.clone();
+        use_effect_with_deps(
+            move |text| {
+                if let Some(input) = input_ref.cast::<web_sys::HtmlInputElement>() {
+                    input.set_value(&mask(text));
+                }
+            },
+            props.text.clone(),
+        );
+    }
 
     let on_focus = {
         let input_ref = input_ref.clone();
@@ -63,18 +221,44 @@ pub fn secret_input(props: &SecretInputProps) -> Html {
     html! {
         <div class="element">
             <input type="password"
-                value={props.text.clone()}
                 id={props.id.clone()}
                 name={props.id.clone()}
                 key={props.id.clone()}
-                oninput={on_input}
                 onfocus={on_focus}
                 onkeydown={on_key_down}
                 ref={input_ref}
                 placeholder={props.hint.clone()}
                 disabled={props.disabled}
+                autocomplete="off"
+                autocapitalize="off"
+                autocorrect="off"
+                spellcheck="false"
                 inputmode={if props.keyboard { "none" } else { "text" }}
             />
         </div>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_deletions() {
+        let cases = [
+            ("deleteContentBackward", false, Some(SecretEdit::DeleteBackward)),
+            ("deleteContentBackward", true, Some(SecretEdit::Clear)),
+            ("deleteContentForward", false, None),
+            ("deleteContentForward", true, Some(SecretEdit::Clear)),
+            ("deleteWordBackward", false, Some(SecretEdit::Clear)),
+            ("deleteWordForward", false, None),
+            ("deleteSoftLineBackward", false, Some(SecretEdit::Clear)),
+            ("deleteHardLineForward", false, None),
+            ("deleteByCut", false, None),
+            ("deleteByCut", true, Some(SecretEdit::Clear)),
+        ];
+        for (input_type, selected, edit) in cases {
+            assert_eq!(delete_edit(input_type, selected), edit, "{} {}", input_type, selected);
+        }
+    }
+}
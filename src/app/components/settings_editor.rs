@@ -0,0 +1,270 @@
+use yew::prelude::*;
+
+use crate::backend::is_tauri;
+use crate::settings::Settings;
+use crate::strength::MIN_SCORES;
+use super::alias_manager::CHARSET_LABELS;
+
+const CHARSETS: [(usize, &str); 3] = [
+    (0, CHARSET_LABELS[0]),
+    (1, CHARSET_LABELS[1]),
+    (2, CHARSET_LABELS[2]),
+];
+const ALIAS_HANDLES: [(usize, &str); 2] = [(0, "Store"), (1, "Don't store")];
+const IDLE_TIMEOUTS: [(Option<u32>, &str); 5] = [
+    (None, "Never"),
+    (Some(60), "1 min"),
+    (Some(300), "5 min"),
+    (Some(900), "15 min"),
+    (Some(1800), "30 min"),
+];
+const PAUSE_TIMEOUTS: [(Option<u32>, &str); 5] = [
+    (Some(0), "Immediately"),
+    (Some(20), "20 s"),
+    (Some(60), "1 min"),
+    (Some(300), "5 min"),
+    (None, "Never"),
+];
+const DELAYS: [(u32, &str); 4] = [(10, "10 s"), (30, "30 s"), (60, "1 min"), (120, "2 min")];
+const WIPE_THRESHOLDS: [(Option<u32>, &str); 4] = [
+    (None, "Never"),
+    (Some(5), "5 attempts"),
+    (Some(10), "10 attempts"),
+    (Some(20), "20 attempts"),
+];
+
+// Dropdown that lets user choose one of preset values
+fn preset_select<T: PartialEq + Copy + 'static>(
+    label: &str,
+    presets: &'static [(T, &'static str)],
+    value: T,
+    on_change: Callback<T>,
+) -> Html {
+    let onchange = Callback::from(move |e: Event| {
+        let select = e.target_dyn_into::<web_sys::HtmlSelectElement>().unwrap();
+        if let Ok(i) = select.value().parse::<usize>() {
+            on_change.emit(presets[i].0);
+        }
+    });
+
+    html! {
+        <label class="setting">
+            <span>{ label }</span>
+            <select {onchange}>
+            {
+                for presets.iter().enumerate().map(|(i, (preset, name))| html! {
+                    <option value={i.to_string()} selected={*preset == value}>{ name }</option>
+                })
+            }
+            </select>
+        </label>
+    }
+}
+
+// Returns a callback that changes a field of edited settings
+fn updater<T: 'static>(draft: &UseStateHandle<Settings>, f: fn(&mut Settings, T)) -> Callback<T> {
+    let draft = draft.clone();
+    Callback::from(move |value| {
+        let mut settings = (*draft).clone();
+        f(&mut settings, value);
+        draft.set(settings);
+    })
+}
+
+fn checkbox(label: &str, checked: bool, on_change: Callback<bool>) -> Html {
+    let onchange = Callback::from(move |e: Event| {
+        let input = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap();
+        on_change.emit(input.checked());
+    });
+
+    html! {
+        <label class="setting">
+            <span>{ label }</span>
+            <input type="checkbox" {checked} {onchange}/>
+        </label>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SettingsEditorProps {
+    pub settings: Settings,
+    pub on_save: Callback<Settings>,
+    pub on_close: Callback<()>,
+}
+
+#[function_component(SettingsEditor)]
+pub fn settings_editor(props: &SettingsEditorProps) -> Html {
+    let draft = use_state(|| props.settings.clone());
+    let wipe_confirmed = use_state(|| false);
+    let names_confirmed = use_state(|| false);
+    // Lowering of master password strength policy is only accepted on second confirmation
+    let mp_policy_confirming = use_state(|| false);
+    let mp_policy_accepted = use_state(|| false);
+
+    // Enabling database wipe (or making it happen sooner) must be confirmed
+    let wipe_needs_confirmation = match (draft.wipe_after_failures, props.settings.wipe_after_failures) {
+        (Some(new), Some(old)) => new < old,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+
+    // Confirmation only holds for the threshold it was given to
+    let on_wipe_after_failures = {
+        let update = updater(&draft, |s, v| s.wipe_after_failures = v);
+        let wipe_confirmed = wipe_confirmed.clone();
+        Callback::from(move |limit| {
+            wipe_confirmed.set(false);
+            update.emit(limit);
+        })
+    };
+
+    // Keeping alias names unencrypted must be confirmed
+    let names_need_confirmation = draft.keep_alias_names && !props.settings.keep_alias_names;
+
+    let mp_policy_lowered = draft.min_mp_score < props.settings.min_mp_score;
+
+    let on_min_mp_score = {
+        let update = updater(&draft, |s, v| s.min_mp_score = v);
+        let mp_policy_confirming = mp_policy_confirming.clone();
+        let mp_policy_accepted = mp_policy_accepted.clone();
+        Callback::from(move |score| {
+            mp_policy_confirming.set(false);
+            mp_policy_accepted.set(false);
+            update.emit(score);
+        })
+    };
+
+    let on_override_mp_policy = {
+        let mp_policy_confirming = mp_policy_confirming.clone();
+        let mp_policy_accepted = mp_policy_accepted.clone();
+        Callback::from(move |_| {
+            if *mp_policy_confirming {
+                mp_policy_accepted.set(true);
+            } else {
+                mp_policy_confirming.set(true);
+            }
+        })
+    };
+
+    let on_cancel_mp_policy_override = {
+        let on_min_mp_score = on_min_mp_score.clone();
+        let min_mp_score = props.settings.min_mp_score;
+        Callback::from(move |_| on_min_mp_score.emit(min_mp_score))
+    };
+
+    let on_save = {
+        let draft = draft.clone();
+        let on_save = props.on_save.clone();
+        Callback::from(move |_| on_save.emit((*draft).clone()))
+    };
+
+    let on_close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_| on_close.emit(()))
+    };
+
+    html! {
+        <>
+            <fieldset>
+                <legend>{ "New aliases" }</legend>
+                { preset_select("Character set", &CHARSETS, draft.charset,
+                    updater(&draft, |s, v| s.charset = v)) }
+                { preset_select("Alias handling", &ALIAS_HANDLES, draft.alias_handle,
+                    updater(&draft, |s, v| s.alias_handle = v)) }
+            </fieldset>
+            <fieldset>
+                <legend>{ "Lock" }</legend>
+                { preset_select("Lock when idle for", &IDLE_TIMEOUTS, draft.idle_timeout_sec,
+                    updater(&draft, |s, v| s.idle_timeout_sec = v)) }
+                { preset_select("Lock in background after", &PAUSE_TIMEOUTS, draft.pause_timeout_sec,
+                    updater(&draft, |s, v| s.pause_timeout_sec = v)) }
+                if is_tauri() {
+                    { checkbox("Lock when window is minimized", draft.lock_on_minimize,
+                        updater(&draft, |s, v| s.lock_on_minimize = v)) }
+                }
+                { preset_select("Wipe aliases after failed unlocks", &WIPE_THRESHOLDS,
+                    draft.wipe_after_failures, on_wipe_after_failures) }
+                if wipe_needs_confirmation {
+                    <div class="element">
+                        { "After this many wrong master passwords in a row all stored aliases \
+                        and their settings will be permanently deleted from this device. \
+                        Make sure you have a backup." }
+                    </div>
+                    { checkbox("I understand", *wipe_confirmed,
+                        Callback::from({
+                            let wipe_confirmed = wipe_confirmed.clone();
+                            move |checked| wipe_confirmed.set(checked)
+                        })) }
+                }
+            </fieldset>
+            <fieldset>
+                <legend>{ "Derived password" }</legend>
+                { preset_select("Hide password after", &DELAYS, draft.password_expiry_sec,
+                    updater(&draft, |s, v| s.password_expiry_sec = v)) }
+                { preset_select("Clear clipboard after", &DELAYS, draft.clipboard_clear_sec,
+                    updater(&draft, |s, v| s.clipboard_clear_sec = v)) }
+                { checkbox("Clear clipboard on lock", draft.clear_clipboard_on_lock,
+                    updater(&draft, |s, v| s.clear_clipboard_on_lock = v)) }
+            </fieldset>
+            <fieldset>
+                <legend>{ "Master password" }</legend>
+                { preset_select("Minimum strength of new master password", &MIN_SCORES,
+                    draft.min_mp_score, on_min_mp_score) }
+                if mp_policy_lowered && !*mp_policy_accepted && !*mp_policy_confirming {
+                    <div class="element">
+                        <button type="button" onclick={on_override_mp_policy.clone()}>
+                            { "Lower it anyway" }
+                        </button>
+                    </div>
+                }
+                if mp_policy_lowered && !*mp_policy_accepted && *mp_policy_confirming {
+                    <div class="element">
+                        { "Weak master password makes all derived passwords easier \
+                            to crack once Psh database leaks. Are you sure?" }
+                    </div>
+                    <div class="element">
+                        <button type="button" onclick={on_override_mp_policy}>
+                            { "Yes, lower it" }
+                        </button>
+                        <button type="button" onclick={on_cancel_mp_policy_override}>
+                            { "Cancel" }
+                        </button>
+                    </div>
+                }
+            </fieldset>
+            <fieldset>
+                <legend>{ "Forgotten master password" }</legend>
+                { checkbox("Keep unencrypted list of alias names", draft.keep_alias_names,
+                    updater(&draft, |s, v| s.keep_alias_names = v)) }
+                if names_need_confirmation {
+                    <div class="element">
+                        { "Alias names will be stored in plain text on this device, so anyone \
+                        with access to it can see which sites you use Psh for." }
+                    </div>
+                    { checkbox("I understand", *names_confirmed,
+                        Callback::from({
+                            let names_confirmed = names_confirmed.clone();
+                            move |checked| names_confirmed.set(checked)
+                        })) }
+                }
+            </fieldset>
+            if cfg!(feature = "keyboard") {
+                <fieldset>
+                    <legend>{ "Keyboard" }</legend>
+                    { checkbox("Show built-in keyboard", draft.kb_visible,
+                        updater(&draft, |s, v| s.kb_visible = v)) }
+                </fieldset>
+            }
+            <div class="element">
+                <button type="button" onclick={on_save} disabled={*draft == props.settings
+                    || (wipe_needs_confirmation && !*wipe_confirmed)
+                    || (names_need_confirmation && !*names_confirmed)
+                    || (mp_policy_lowered && !*mp_policy_accepted)}
+                >
+                    { "Save" }
+                </button>
+                <button type="button" onclick={on_close}>{ "Back" }</button>
+            </div>
+        </>
+    }
+}
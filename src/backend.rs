@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use yew::Callback;
+
+pub use psh_gui_common::{
+    charset_by_id, charset_id, check_records, import_preview, AliasInfo, BackupFile,
+    ImportPreview, Passphrase, PasswordChange, Secret, UnlockError, Username, BACKUP_VERSION, DB_VERSION,
+};
+
+pub mod tauri;
+pub mod web;
+// Worker side is only reachable from the worker entry point, not from the app binary
+#[allow(dead_code)]
+pub mod worker;
+
+use self::tauri::TauriBackend;
+use web::WebBackend;
+
+/// Saves text file chosen by user (Tauri app) or offers to download it.
+/// Returns `false` if user cancelled saving.
+pub async fn save_text_file(file_name: &str, contents: String) -> Result<bool, String> {
+    if is_tauri() {
+        tauri::save_text_file(file_name, contents).await
+    } else {
+        web::save_file(file_name, "text/plain", &contents)
+            .map(|_| true)
+            .map_err(|e| format!("{:?}", e))
+    }
+}
+
+pub fn is_tauri() -> bool {
+    web_sys::window().unwrap()
+        .get("__TAURI__")
+        .is_some()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum PshRequest {
+    CheckDb,
+    Unlock { master_password: Secret },
+    DerivePassword {
+        alias: String,
+        secret: Option<Secret>,
+        charset: usize,
+        length: Option<usize>,
+        generation: u32,
+        passphrase: Option<Passphrase>,
+    },
+    // Derive username of alias that uses derived one
+    DeriveUsername { alias: String },
+    // Store password of alias (it's added if doesn't exist)
+    StorePassword { alias: String, password: Secret },
+    AppendAlias {
+        alias: String,
+        use_secret: bool,
+        charset: usize,
+        length: Option<usize>,
+        passphrase: Option<Passphrase>,
+    },
+    RemoveAlias { alias: String },
+    RemoveAliases { aliases: Vec<String> },
+    // Replace alias with another one (possibly with the same name but different settings)
+    UpdateAlias { alias: String, new: AliasInfo },
+    // Re-encrypt database with new master password (kept aside until confirmed)
+    // and derive passwords that change along with it
+    PreviewMasterPasswordChange { old_password: Secret, new_password: Secret },
+    // Save database re-encrypted on preview
+    ConfirmMasterPasswordChange,
+    CancelMasterPasswordChange,
+    // Export database to a file (encrypted with master password if passphrase isn't set)
+    Export { passphrase: Option<Secret> },
+    // Read backup file encrypted with given passphrase (or master password)
+    Import { passphrase: Secret },
+    // Add new aliases and replace conflicting ones with imported ones
+    ApplyImport { new: Vec<AliasInfo>, replace: Vec<AliasInfo> },
+    // Delete psh database (e.g. after too many failed unlock attempts)
+    WipeDb,
+    Lock,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum PshResponse {
+    DbExists(bool),
+    Unlocked(Vec<AliasInfo>),
+    UnlockFailed(UnlockError),
+    Password(Secret),
+    Username(String),
+    Aliases(Vec<AliasInfo>),
+    MasterPasswordChangePreview(Vec<PasswordChange>),
+    MasterPasswordChanged,
+    MasterPasswordWrong,
+    // Whether database was exported (false if user cancelled export)
+    Exported(bool),
+    ImportPreview(ImportPreview),
+    ImportFailed(String),
+    ImportCancelled,
+    DbWiped,
+    Failed(String),
+}
+
+/// Psh backend. Serves [`PshRequest`]s either with native Psh of Tauri app
+/// or with in-browser Psh running in a Web Worker.
+pub enum Backend {
+    Web(WebBackend),
+    Tauri(TauriBackend),
+}
+
+impl Backend {
+    pub fn new(on_response: Callback<PshResponse>) -> Self {
+        if is_tauri() {
+            Self::Tauri(TauriBackend::new(on_response))
+        } else {
+            Self::Web(WebBackend::new(on_response))
+        }
+    }
+
+    pub fn send(&self, request: PshRequest) {
+        match self {
+            Self::Web(backend) => backend.send(request),
+            Self::Tauri(backend) => backend.send(request),
+        }
+    }
+
+    /// Abandons ongoing operation (if any). Response to abandoned operation is never delivered.
+    pub fn reset(&mut self) {
+        match self {
+            Self::Web(backend) => backend.reset(),
+            Self::Tauri(backend) => backend.reset(),
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use yew::Callback;
+use yew::platform::spawn_local;
+
+use psh::PshStore;
+use psh_webdb::PshWebDb;
+
+use super::web;
+use super::{AliasInfo, ImportPreview, Passphrase, PasswordChange, PshRequest, PshResponse, Secret, UnlockError};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "tauri"], js_name = invoke, catch)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"], js_name = listen)]
+    fn tauri_listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>) -> js_sys::Promise;
+}
+
+// Event emitted by Tauri app when its window gets minimized
+const WINDOW_MINIMIZED_EVENT: &str = "window-minimized";
+
+// Arguments of Tauri commands (Tauri expects them in camelCase)
+#[derive(Serialize)]
+struct NoArgs {}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportWebDbArgs {
+    records: Vec<String>,
+    meta: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnlockArgs {
+    master_password: Secret,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DerivePasswordArgs {
+    alias: String,
+    secret: Option<Secret>,
+    charset: usize,
+    length: Option<usize>,
+    generation: u32,
+    passphrase: Option<Passphrase>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DeriveUsernameArgs {
+    alias: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StorePasswordArgs {
+    alias: String,
+    password: Secret,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppendAliasArgs {
+    alias: String,
+    use_secret: bool,
+    charset: usize,
+    length: Option<usize>,
+    passphrase: Option<Passphrase>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoveAliasArgs {
+    alias: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoveAliasesArgs {
+    aliases: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateAliasArgs {
+    alias: String,
+    new: AliasInfo,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PreviewMasterPasswordChangeArgs {
+    old_password: Secret,
+    new_password: Secret,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportDbArgs {
+    passphrase: Option<Secret>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadImportArgs {
+    passphrase: Secret,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ApplyImportArgs {
+    new: Vec<AliasInfo>,
+    replace: Vec<AliasInfo>,
+}
+
+async fn invoke<A: Serialize, T: DeserializeOwned>(cmd: &str, args: &A) -> Result<T, String> {
+    let args = serde_wasm_bindgen::to_value(args).unwrap();
+    match tauri_invoke(cmd, args).await {
+        Ok(value) => serde_wasm_bindgen::from_value(value).map_err(|e| e.to_string()),
+        Err(e) => Err(e.as_string().unwrap_or_else(|| format!("{:?}", e))),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadConfigArgs<'a> {
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WriteConfigArgs<'a> {
+    name: &'a str,
+    contents: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveTextFileArgs<'a> {
+    file_name: &'a str,
+    contents: String,
+}
+
+/// Asks user where to save text file and saves it. Returns `false` if user cancelled saving.
+pub async fn save_text_file(file_name: &str, contents: String) -> Result<bool, String> {
+    invoke("save_text_file", &SaveTextFileArgs { file_name, contents }).await
+}
+
+/// Reads Tauri app config file with given name.
+pub async fn read_config(name: &str) -> Result<Option<String>, String> {
+    invoke("read_config", &ReadConfigArgs { name }).await
+}
+
+/// Writes Tauri app config file with given name.
+pub async fn write_config(name: &str, contents: String) -> Result<(), String> {
+    invoke("write_config", &WriteConfigArgs { name, contents }).await
+}
+
+/// Calls `callback` every time Tauri app window gets minimized.
+pub fn on_window_minimized(callback: Callback<()>) {
+    let handler = Closure::new(move |_| callback.emit(()));
+    let _ = tauri_listen(WINDOW_MINIMIZED_EVENT, &handler);
+    // Listener lives as long as the app does
+    handler.forget();
+}
+
+/// Psh backend that delegates everything to native Psh of Tauri app.
+pub struct TauriBackend {
+    on_response: Callback<PshResponse>,
+    // Responses to requests of previous generations are dropped
+    generation: Rc<Cell<u32>>,
+}
+
+impl TauriBackend {
+    pub fn new(on_response: Callback<PshResponse>) -> Self {
+        Self { on_response, generation: Rc::new(Cell::new(0)) }
+    }
+
+    pub fn send(&self, request: PshRequest) {
+        let on_response = self.on_response.clone();
+        let generation = self.generation.clone();
+        let request_generation = generation.get();
+        spawn_local(async move {
+            if let Some(response) = process(request).await {
+                if generation.get() == request_generation {
+                    on_response.emit(response);
+                }
+            }
+        });
+    }
+
+    // Native Psh can't be interrupted so we just ignore the outcome and lock it afterwards
+    pub fn reset(&mut self) {
+        self.generation.set(self.generation.get() + 1);
+        self.send(PshRequest::Lock);
+    }
+}
+
+// Moves Psh database from webview storage (used by earlier app versions) to app data dir.
+// Webview copy is deleted once imported, so that it can't come back after database wipe.
+async fn migrate_webdb() -> Result<bool, String> {
+    web::finish_pending_replace().map_err(|e| format!("Failed to finish database update: {}", e))?;
+    let webdb = PshWebDb::new();
+    if !webdb.exists() {
+        return Ok(false);
+    }
+    let records = webdb.records()
+        .map(|r| r.to_string())
+        .collect();
+    let meta = web::load_meta().map_err(|e| e.to_string())?;
+    invoke::<_, ()>("import_webdb", &ImportWebDbArgs { records, meta }).await?;
+    web::wipe_db().map_err(|e| format!("Failed to delete migrated database: {}", e))?;
+    Ok(true)
+}
+
+async fn process(request: PshRequest) -> Option<PshResponse> {
+    match request {
+        PshRequest::CheckDb => {
+            let res = match invoke::<_, bool>("db_exists", &NoArgs {}).await {
+                Ok(false) => migrate_webdb().await,
+                res => res,
+            };
+            Some(match res {
+                Ok(exists) => PshResponse::DbExists(exists),
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::Unlock { master_password } => {
+            let args = UnlockArgs { master_password };
+            let res = invoke::<_, Result<Vec<AliasInfo>, UnlockError>>("unlock", &args).await;
+            Some(match res {
+                Ok(Ok(aliases)) => PshResponse::Unlocked(aliases),
+                Ok(Err(e)) => PshResponse::UnlockFailed(e),
+                Err(e) => PshResponse::UnlockFailed(UnlockError::Other(e)),
+            })
+        }
+        PshRequest::DerivePassword { alias, secret, charset, length, generation, passphrase } => {
+            let args = DerivePasswordArgs { alias, secret, charset, length, generation, passphrase };
+            let res = invoke::<_, Secret>("derive_password", &args).await;
+            Some(match res {
+                Ok(pass) => PshResponse::Password(pass),
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::DeriveUsername { alias } => {
+            let res = invoke::<_, String>("derive_username", &DeriveUsernameArgs { alias }).await;
+            Some(match res {
+                Ok(username) => PshResponse::Username(username),
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::StorePassword { alias, password } => {
+            let args = StorePasswordArgs { alias, password };
+            let res = invoke::<_, Vec<AliasInfo>>("store_password", &args).await;
+            Some(match res {
+                Ok(aliases) => PshResponse::Aliases(aliases),
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::AppendAlias { alias, use_secret, charset, length, passphrase } => {
+            let args = AppendAliasArgs { alias, use_secret, charset, length, passphrase };
+            let res = invoke::<_, Vec<AliasInfo>>("append_alias", &args).await;
+            Some(match res {
+                Ok(aliases) => PshResponse::Aliases(aliases),
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::RemoveAlias { alias } => {
+            let res = invoke::<_, Vec<AliasInfo>>("remove_alias", &RemoveAliasArgs { alias }).await;
+            Some(match res {
+                Ok(aliases) => PshResponse::Aliases(aliases),
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::RemoveAliases { aliases } => {
+            let res = invoke::<_, Vec<AliasInfo>>("remove_aliases", &RemoveAliasesArgs { aliases }).await;
+            Some(match res {
+                Ok(aliases) => PshResponse::Aliases(aliases),
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::UpdateAlias { alias, new } => {
+            let args = UpdateAliasArgs { alias, new: AliasInfo { password: None, ..new } };
+            let res = invoke::<_, Vec<AliasInfo>>("update_alias", &args).await;
+            Some(match res {
+                Ok(aliases) => PshResponse::Aliases(aliases),
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::PreviewMasterPasswordChange { old_password, new_password } => {
+            let args = PreviewMasterPasswordChangeArgs { old_password, new_password };
+            // `None` means that old master password is wrong
+            let res = invoke::<_, Option<Vec<PasswordChange>>>("preview_master_password_change", &args).await;
+            Some(match res {
+                Ok(Some(changes)) => PshResponse::MasterPasswordChangePreview(changes),
+                Ok(None) => PshResponse::MasterPasswordWrong,
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::ConfirmMasterPasswordChange => {
+            let res = invoke::<_, ()>("confirm_master_password_change", &NoArgs {}).await;
+            Some(match res {
+                Ok(()) => PshResponse::MasterPasswordChanged,
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::CancelMasterPasswordChange => {
+            invoke::<_, ()>("cancel_master_password_change", &NoArgs {}).await.ok();
+            None
+        }
+        PshRequest::Export { passphrase } => {
+            // `false` means that user cancelled export
+            let res = invoke::<_, bool>("export_db", &ExportDbArgs { passphrase }).await;
+            Some(match res {
+                Ok(exported) => PshResponse::Exported(exported),
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::Import { passphrase } => {
+            // `None` means that user cancelled import
+            let res = invoke::<_, Option<ImportPreview>>("read_import", &ReadImportArgs { passphrase }).await;
+            Some(match res {
+                Ok(Some(preview)) => PshResponse::ImportPreview(preview),
+                Ok(None) => PshResponse::ImportCancelled,
+                Err(e) => PshResponse::ImportFailed(e),
+            })
+        }
+        PshRequest::ApplyImport { new, replace } => {
+            let res = invoke::<_, Vec<AliasInfo>>("apply_import", &ApplyImportArgs { new, replace }).await;
+            Some(match res {
+                Ok(aliases) => PshResponse::Aliases(aliases),
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::WipeDb => {
+            // Webview copy may be left behind if migration failed halfway
+            let res = invoke::<_, ()>("wipe_db", &NoArgs {}).await
+                .and_then(|_| web::wipe_db().map_err(|e| e.to_string()));
+            // Neither copy may be found by the next database check
+            let res = match res {
+                Ok(()) => match invoke::<_, bool>("db_exists", &NoArgs {}).await {
+                    Ok(false) if !PshWebDb::new().exists() => Ok(()),
+                    Ok(_) => Err("Database still exists after wipe".to_string()),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+            Some(match res {
+                Ok(()) => PshResponse::DbWiped,
+                Err(e) => PshResponse::Failed(e),
+            })
+        }
+        PshRequest::Lock => {
+            invoke::<_, ()>("lock", &NoArgs {}).await.ok();
+            None
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail};
+use gloo_events::EventListener;
+use gloo_timers::callback::Timeout;
+use wasm_bindgen::{JsCast, JsValue, UnwrapThrowExt};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, HtmlInputElement, MessageEvent, Storage, Url, Worker};
+use yew::Callback;
+use yew::platform::spawn_local;
+
+use serde::{Deserialize, Serialize};
+
+use psh::{PshStore, ZeroizingString};
+use psh_webdb::PshWebDb;
+
+use super::worker::{StoreOp, WorkerInput, WorkerOutput};
+use super::{check_records, PshRequest, PshResponse, Secret, UnlockError, DB_VERSION};
+
+// Path to worker script relative to app's index.html
+const WORKER_PATH: &str = "worker.js";
+// Suggested name of exported database file
+const BACKUP_FILE_NAME: &str = "psh-backup.json";
+// Web Storage key of psh database version
+const DB_VERSION_KEY: &str = "psh-gui-db-version";
+// Web Storage key of records that replace the whole psh database
+const DB_STAGING_KEY: &str = "psh-gui-db-staging";
+// Web Storage key of sealed alias settings
+const META_KEY: &str = "psh-gui-alias-settings";
+
+/// Psh backend that runs Psh in a dedicated Web Worker so that resource-intensive
+/// operations (Argon2 hashing in the first place) don't freeze the UI.
+pub struct WebBackend {
+    worker: PshWorkerHandle,
+    on_response: Callback<PshResponse>,
+}
+
+impl WebBackend {
+    pub fn new(on_response: Callback<PshResponse>) -> Self {
+        let worker = spawn_worker(on_response.clone());
+        Self { worker, on_response }
+    }
+
+    pub fn send(&self, request: PshRequest) {
+        match request {
+            PshRequest::CheckDb => {
+                self.on_response.emit(PshResponse::DbExists(PshWebDb::new().exists()));
+                return;
+            }
+            PshRequest::Unlock { .. } => {
+                // Web Worker has no access to Web Storage so we pass db records along
+                match load_db() {
+                    Ok((records, meta)) => self.worker.send(WorkerInput::Load { records, meta }),
+                    Err(e) => {
+                        self.on_response.emit(PshResponse::UnlockFailed(e));
+                        return;
+                    }
+                }
+            }
+            PshRequest::Import { passphrase } => {
+                import_file(self.worker.clone(), self.on_response.clone(), passphrase);
+                return;
+            }
+            PshRequest::WipeDb => {
+                let response = match wipe_db() {
+                    Ok(()) => PshResponse::DbWiped,
+                    Err(e) => PshResponse::Failed(e.to_string()),
+                };
+                self.on_response.emit(response);
+                return;
+            }
+            _ => {}
+        }
+        self.worker.send(WorkerInput::Request(request));
+    }
+
+    // Stops the worker (along with Argon2 hashing it may be busy with)
+    // and replaces it with a fresh one
+    pub fn reset(&mut self) {
+        self.worker.terminate();
+        self.worker = spawn_worker(self.on_response.clone());
+    }
+}
+
+// Dedicated Web Worker running Psh
+#[derive(Clone)]
+struct PshWorkerHandle {
+    worker: Worker,
+    // Input sent before the worker got loaded (`None` once it's loaded)
+    pending: Rc<RefCell<Option<Vec<WorkerInput>>>>,
+    _listener: Rc<EventListener>,
+}
+
+impl PshWorkerHandle {
+    fn send(&self, input: WorkerInput) {
+        match self.pending.borrow_mut().as_mut() {
+            Some(pending) => pending.push(input),
+            None => post_input(&self.worker, input),
+        }
+    }
+
+    fn terminate(&self) {
+        self.worker.terminate();
+    }
+}
+
+fn post_input(worker: &Worker, input: WorkerInput) {
+    let msg = serde_wasm_bindgen::to_value(&input).expect_throw("failed to encode worker input");
+    worker.post_message(&msg).expect_throw("failed to post worker input");
+}
+
+// Worker script is a wasm-bindgen shim that has to be told where its module is
+fn create_worker() -> Worker {
+    let location = web_sys::window().unwrap().location().href().unwrap();
+    let shim_url = String::from(
+        Url::new_with_base(WORKER_PATH, &location)
+            .expect_throw("failed to resolve worker script url")
+            .to_string(),
+    );
+    let wasm_url = format!(
+        "{}_bg.wasm",
+        shim_url.strip_suffix(".js").expect_throw("worker script url must end with .js"),
+    );
+    let script = format!(r#"importScripts("{}");wasm_bindgen("{}");"#, shim_url, wasm_url);
+
+    let parts = js_sys::Array::of1(&JsValue::from_str(&script));
+    let options = BlobPropertyBag::new();
+    options.set_type("application/javascript");
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options).unwrap();
+    let url = Url::create_object_url_with_blob(&blob).unwrap();
+    Worker::new(&url).expect_throw("failed to spawn worker")
+}
+
+fn spawn_worker(on_response: Callback<PshResponse>) -> PshWorkerHandle {
+    let worker = create_worker();
+    let pending = Rc::new(RefCell::new(Some(Vec::new())));
+    let listener = {
+        let worker = worker.clone();
+        let pending = pending.clone();
+        EventListener::new(&worker.clone(), "message", move |e| {
+            let e: &MessageEvent = e.unchecked_ref();
+            let Ok(output) = serde_wasm_bindgen::from_value(e.data()) else {
+                return;
+            };
+            match output {
+                WorkerOutput::Loaded => {
+                    for input in pending.borrow_mut().take().unwrap_or_default() {
+                        post_input(&worker, input);
+                    }
+                }
+                WorkerOutput::Persist(ops) => {
+                    if persist(ops).is_err() {
+                        on_response.emit(PshResponse::Failed("Failed to save changes".to_string()));
+                    }
+                }
+                WorkerOutput::Export(contents) => {
+                    if save_file(BACKUP_FILE_NAME, "application/json", &contents).is_ok() {
+                        on_response.emit(PshResponse::Exported(true));
+                    } else {
+                        on_response.emit(PshResponse::Failed("Failed to save backup file".to_string()));
+                    }
+                }
+                WorkerOutput::Response(response) => on_response.emit(response),
+            }
+        })
+    };
+    PshWorkerHandle { worker, pending, _listener: Rc::new(listener) }
+}
+
+// Lets user choose backup file and passes its contents to the worker
+fn import_file(
+    worker: PshWorkerHandle,
+    on_response: Callback<PshResponse>,
+    passphrase: Secret,
+) {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let input: HtmlInputElement = document.create_element("input").unwrap().unchecked_into();
+    input.set_type("file");
+    input.set_accept(".json,application/json");
+
+    let on_cancel = on_response.clone();
+    EventListener::once(&input, "change", move |e| {
+        let input: HtmlInputElement = e.target().unwrap().unchecked_into();
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            on_response.emit(PshResponse::ImportCancelled);
+            return;
+        };
+        spawn_local(async move {
+            match JsFuture::from(file.text()).await {
+                Ok(contents) => {
+                    let contents = contents.as_string().unwrap_or_default();
+                    worker.send(WorkerInput::Import { contents, passphrase });
+                }
+                Err(_) => {
+                    on_response.emit(PshResponse::ImportFailed("Failed to read file".to_string()));
+                }
+            }
+        });
+    }).forget();
+    // Not all browsers fire this one
+    EventListener::once(&input, "cancel", move |_| {
+        on_cancel.emit(PshResponse::ImportCancelled);
+    }).forget();
+
+    input.click();
+}
+
+/// Offers user to download file with given contents.
+pub fn save_file(file_name: &str, mime_type: &str, contents: &str) -> Result<(), JsValue> {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let options = BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let link: HtmlAnchorElement = document.create_element("a")?.unchecked_into();
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+
+    // Give browser a chance to start the download before releasing the blob
+    Timeout::new(0, move || {
+        Url::revoke_object_url(&url).ok();
+    }).forget();
+    Ok(())
+}
+
+// Applies changes made to worker's in-memory db to the persistent one
+fn persist(ops: Vec<StoreOp>) -> anyhow::Result<()> {
+    let mut db = PshWebDb::new();
+    for op in ops {
+        match op {
+            StoreOp::Append(record) => db.append(&ZeroizingString::new(record))?,
+            StoreOp::Delete(record) => db.delete(&ZeroizingString::new(record))?,
+            StoreOp::Replace { records, meta } => replace(&mut db, Staged { records, meta })?,
+            StoreOp::Meta(meta) => save_meta(&local_storage()?, &meta)?,
+        }
+    }
+    Ok(())
+}
+
+/// Reads sealed alias settings (if any).
+pub fn load_meta() -> anyhow::Result<Option<String>> {
+    local_storage()?.get_item(META_KEY)
+        .map_err(|_| anyhow!("Access to Web Storage is denied"))
+}
+
+fn save_meta(storage: &Storage, meta: &str) -> anyhow::Result<()> {
+    storage.set_item(META_KEY, meta)
+        .map_err(|_| anyhow!("Web Storage is full or access to it is denied"))
+}
+
+// Reads psh database records along with sealed alias settings
+// and makes sure they can be unlocked
+fn load_db() -> Result<(Vec<String>, Option<String>), UnlockError> {
+    let storage = local_storage()
+        .map_err(|e| UnlockError::StorageFailure(e.to_string()))?;
+    let version = storage.get_item(DB_VERSION_KEY).ok().flatten();
+    match version.map(|v| v.parse::<u32>()) {
+        Some(Ok(version)) if version > DB_VERSION => {
+            return Err(UnlockError::UnsupportedDbVersion(version));
+        }
+        Some(Ok(_)) => {}
+        // Databases of earlier app versions have no version mark.
+        // Setting it also makes sure that storage is writable.
+        _ => storage.set_item(DB_VERSION_KEY, &DB_VERSION.to_string())
+            .map_err(|_| UnlockError::StorageFailure(
+                "Web Storage is full or access to it is denied".to_string()
+            ))?,
+    }
+    finish_replace(&storage)
+        .map_err(|e| UnlockError::StorageFailure(format!("Failed to finish database update: {}", e)))?;
+    let records: Vec<String> = PshWebDb::new().records()
+        .map(|r| r.to_string())
+        .collect();
+    check_records(&records)?;
+    let meta = load_meta().map_err(|e| UnlockError::StorageFailure(e.to_string()))?;
+    Ok((records, meta))
+}
+
+/// Deletes psh database from Web Storage.
+pub fn wipe_db() -> anyhow::Result<()> {
+    let mut db = PshWebDb::new();
+    let records: Vec<ZeroizingString> = db.records().collect();
+    write_records(&mut db, &records, Vec::new())?;
+    if let Ok(storage) = local_storage() {
+        storage.remove_item(DB_STAGING_KEY).ok();
+        storage.remove_item(META_KEY).ok();
+        storage.remove_item(DB_VERSION_KEY).ok();
+    }
+    // Database check must not find it anymore
+    if db.exists() {
+        bail!("Database still exists after wipe");
+    }
+    Ok(())
+}
+
+fn local_storage() -> anyhow::Result<Storage> {
+    web_sys::window().unwrap()
+        .local_storage().ok().flatten()
+        .ok_or_else(|| anyhow!("Web Storage is not available"))
+}
+
+// Records that replace the whole psh database along with alias settings sealed with them
+#[derive(Serialize, Deserialize)]
+struct Staged {
+    records: Vec<String>,
+    meta: String,
+}
+
+// Web Storage has no transactions, so new records are staged first and old ones
+// are kept until the new ones are written and verified. If the page gets closed
+// halfway through, replacement is finished on next load.
+fn replace(db: &mut PshWebDb, staged: Staged) -> anyhow::Result<()> {
+    let storage = local_storage()?;
+    storage.set_item(DB_STAGING_KEY, &serde_json::to_string(&staged)?)
+        .map_err(|_| anyhow!("Web Storage is full or access to it is denied"))?;
+    let old_records: Vec<String> = db.records().map(|r| r.to_string()).collect();
+    let old_meta = load_meta()?;
+    let res = write_verified(db, staged.records).and_then(|_| save_meta(&storage, &staged.meta));
+    if res.is_err() {
+        // Staged records are left in place if old ones can't be restored either
+        write_verified(db, old_records)?;
+        match old_meta {
+            Some(meta) => save_meta(&storage, &meta)?,
+            None => storage.remove_item(META_KEY)
+                .map_err(|_| anyhow!("Access to Web Storage is denied"))?,
+        }
+    }
+    storage.remove_item(DB_STAGING_KEY)
+        .map_err(|_| anyhow!("Failed to remove staged database records"))?;
+    res
+}
+
+/// Completes replacement of database records interrupted by closing the page (if any).
+pub fn finish_pending_replace() -> anyhow::Result<()> {
+    finish_replace(&local_storage()?)
+}
+
+fn finish_replace(storage: &Storage) -> anyhow::Result<()> {
+    let staged = storage.get_item(DB_STAGING_KEY)
+        .map_err(|_| anyhow!("Access to Web Storage is denied"))?;
+    if let Some(staged) = staged {
+        let staged: Staged = serde_json::from_str(&staged)?;
+        write_verified(&mut PshWebDb::new(), staged.records)?;
+        save_meta(storage, &staged.meta)?;
+        storage.remove_item(DB_STAGING_KEY)
+            .map_err(|_| anyhow!("Failed to remove staged database records"))?;
+    }
+    Ok(())
+}
+
+// Replaces all database records and makes sure they were written as is
+fn write_verified(db: &mut PshWebDb, mut records: Vec<String>) -> anyhow::Result<()> {
+    let current_records: Vec<ZeroizingString> = db.records().collect();
+    write_records(db, &current_records, records.clone())?;
+    let mut written: Vec<String> = db.records().map(|r| r.to_string()).collect();
+    written.sort();
+    records.sort();
+    if written != records {
+        bail!("Database records differ from the written ones");
+    }
+    Ok(())
+}
+
+fn write_records(
+    db: &mut PshWebDb,
+    old_records: &[ZeroizingString],
+    records: Vec<String>,
+) -> anyhow::Result<()> {
+    for record in old_records {
+        db.delete(record)?;
+    }
+    for record in records {
+        db.append(&ZeroizingString::new(record))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,386 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{DedicatedWorkerGlobalScope, MessageEvent};
+
+use psh::{Psh, PshStore, ZeroizingString};
+use psh_gui_common::alias_meta::MetaStore;
+use psh_gui_common::aliases::{
+    self, aliases, aliases_with_passwords, append_alias, copy_aliases, password_changes, remove_alias, update_alias,
+};
+use psh_gui_common::psh_secret;
+
+use super::{
+    charset_by_id, import_preview, AliasInfo, BackupFile, PshRequest, PshResponse, Secret, UnlockError,
+    BACKUP_VERSION,
+};
+
+// A change made to Psh database
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum StoreOp {
+    Append(String),
+    Delete(String),
+    // Replace all the records (and alias settings sealed along with them) at once
+    Replace { records: Vec<String>, meta: String },
+    // Replace sealed alias settings
+    Meta(String),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum WorkerInput {
+    // Psh database records to unlock Psh with along with sealed alias settings
+    Load { records: Vec<String>, meta: Option<String> },
+    // Backup file contents along with its password
+    Import { contents: String, passphrase: Secret },
+    Request(PshRequest),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum WorkerOutput {
+    // Worker is ready to receive input
+    Loaded,
+    // Changes to be applied to persistent Psh database
+    Persist(Vec<StoreOp>),
+    // Backup file contents to be saved
+    Export(String),
+    Response(PshResponse),
+}
+
+// In-memory Psh database that journals all the changes made to it
+#[derive(Clone, Default)]
+struct MemoryStore {
+    records: Rc<RefCell<Vec<String>>>,
+    journal: Rc<RefCell<Vec<StoreOp>>>,
+}
+
+impl MemoryStore {
+    fn new(records: Vec<String>) -> Self {
+        Self {
+            records: Rc::new(RefCell::new(records)),
+            journal: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    // Returns a standalone copy of the store
+    fn snapshot(&self) -> Self {
+        Self::new(self.records.borrow().clone())
+    }
+}
+
+impl PshStore for MemoryStore {
+    fn exists(&self) -> bool {
+        !self.records.borrow().is_empty()
+    }
+
+    fn records(&self) -> Box<dyn Iterator<Item = ZeroizingString>> {
+        Box::new(
+            self.records.borrow().clone()
+                .into_iter()
+                .map(ZeroizingString::new)
+        )
+    }
+
+    fn append(&mut self, record: &ZeroizingString) -> anyhow::Result<()> {
+        self.records.borrow_mut().push(record.to_string());
+        self.journal.borrow_mut().push(StoreOp::Append(record.to_string()));
+        Ok(())
+    }
+
+    fn delete(&mut self, record: &ZeroizingString) -> anyhow::Result<()> {
+        let record = record.to_string();
+        self.records.borrow_mut().retain(|r| *r != record);
+        self.journal.borrow_mut().push(StoreOp::Delete(record));
+        Ok(())
+    }
+}
+
+/// Web Worker that owns Psh instance and serves [`PshRequest`]s.
+pub struct PshWorker {
+    psh: Option<Psh>,
+    // Psh database
+    db: MemoryStore,
+    // Sealed alias settings to unlock along with Psh
+    meta_record: Option<String>,
+    meta: MetaStore,
+    // Psh with database and alias settings re-encrypted with new master password
+    // (until change is confirmed)
+    rekeyed: Option<(Psh, MemoryStore, String)>,
+    // Stored passwords of aliases read from backup file (until import is applied)
+    imported_passwords: HashMap<String, Secret>,
+}
+
+// Creates a copy of Psh database encrypted with another master password
+fn rekey(psh: &Psh, new_password: Secret) -> anyhow::Result<(Psh, MemoryStore)> {
+    let db = MemoryStore::default();
+    let mut new_psh = Psh::new(psh_secret(new_password), db.clone())?;
+    copy_aliases(psh, &mut new_psh)?;
+    Ok((new_psh, db))
+}
+
+impl PshWorker {
+    fn aliases(&self) -> Vec<AliasInfo> {
+        aliases(self.psh.as_ref().unwrap(), &self.meta)
+    }
+
+    fn unlock(&mut self, psh: Psh) -> anyhow::Result<()> {
+        self.meta = MetaStore::load(&psh, self.meta_record.as_deref())?;
+        self.psh = Some(psh);
+        Ok(())
+    }
+
+    // Journals sealed alias settings to be saved along with Psh database
+    fn save_meta(&self) -> anyhow::Result<()> {
+        let record = self.meta.seal(self.psh.as_ref().unwrap())?;
+        self.db.journal.borrow_mut().push(StoreOp::Meta(record));
+        Ok(())
+    }
+
+    fn export(&self, passphrase: Option<Secret>) -> anyhow::Result<String> {
+        let psh = self.psh.as_ref().unwrap();
+        let (records, meta) = match passphrase {
+            Some(passphrase) => {
+                let (new_psh, db) = rekey(psh, passphrase)?;
+                let records = db.records.borrow().clone();
+                (records, self.meta.seal(&new_psh)?)
+            }
+            None => (self.db.records.borrow().clone(), self.meta.seal(psh)?),
+        };
+        let backup = BackupFile { version: BACKUP_VERSION, records, meta };
+        Ok(serde_json::to_string(&backup)?)
+    }
+
+    fn read_import(&mut self, contents: String, passphrase: Secret) -> PshResponse {
+        let backup: BackupFile = match serde_json::from_str(&contents) {
+            Ok(backup) => backup,
+            Err(_) => return PshResponse::ImportFailed("Not a Psh backup file".to_string()),
+        };
+        if backup.version > BACKUP_VERSION {
+            return PshResponse::ImportFailed("Unsupported backup file version".to_string());
+        }
+        let db = MemoryStore::new(backup.records);
+        let imported = match Psh::new(psh_secret(passphrase), db) {
+            Ok(imported) => imported,
+            Err(_) => return PshResponse::ImportFailed("Wrong backup password".to_string()),
+        };
+        match MetaStore::load(&imported, Some(&backup.meta)) {
+            Ok(imported_meta) => {
+                let current = aliases_with_passwords(self.psh.as_ref().unwrap(), &self.meta);
+                let imported = aliases_with_passwords(&imported, &imported_meta);
+                let mut preview = import_preview(&current, imported);
+                self.imported_passwords = preview.take_passwords();
+                PshResponse::ImportPreview(preview)
+            }
+            Err(_) => PshResponse::ImportFailed("Alias settings in backup file are damaged".to_string()),
+        }
+    }
+
+    fn process(&mut self, request: PshRequest) -> Option<PshResponse> {
+        match request {
+            // Db is checked and wiped by the main thread that has access to Web Storage
+            PshRequest::CheckDb | PshRequest::WipeDb => unreachable!(),
+            PshRequest::Unlock { master_password } => {
+                match Psh::new(psh_secret(master_password), self.db.clone()) {
+                    Ok(psh) => match self.unlock(psh) {
+                        Ok(()) => Some(PshResponse::Unlocked(self.aliases())),
+                        Err(e) => Some(PshResponse::UnlockFailed(UnlockError::CorruptDb(e.to_string()))),
+                    },
+                    // Records were checked on load, so Psh can only fail to decrypt them
+                    Err(_) if self.db.exists() => Some(PshResponse::UnlockFailed(UnlockError::WrongPassword)),
+                    Err(e) => Some(PshResponse::UnlockFailed(UnlockError::Other(e.to_string()))),
+                }
+            }
+            PshRequest::DerivePassword { alias, secret, charset, length, generation, passphrase } => {
+                let psh = self.psh.as_ref().unwrap();
+                match charset_by_id(charset) {
+                    Ok(charset) => {
+                        let password = aliases::password(
+                            psh, &self.meta, &alias, secret, charset, length, generation, passphrase.as_ref(),
+                        );
+                        Some(PshResponse::Password(password))
+                    }
+                    Err(e) => Some(PshResponse::Failed(e.to_string())),
+                }
+            }
+            PshRequest::DeriveUsername { alias } => {
+                let psh = self.psh.as_ref().unwrap();
+                Some(PshResponse::Username(aliases::username(psh, &alias)))
+            }
+            PshRequest::AppendAlias { alias, use_secret, charset, length, passphrase } => {
+                let psh = self.psh.as_mut().unwrap();
+                let info = AliasInfo { alias, use_secret, charset, length, passphrase, ..Default::default() };
+                let res = append_alias(psh, &mut self.meta, info).and_then(|_| self.save_meta());
+                if res.is_ok() {
+                    Some(PshResponse::Aliases(self.aliases()))
+                } else {
+                    Some(PshResponse::Failed("Failed to save alias".to_string()))
+                }
+            }
+            PshRequest::StorePassword { alias, password } => {
+                let psh = self.psh.as_mut().unwrap();
+                let res = aliases::store_password(psh, &mut self.meta, alias, password)
+                    .and_then(|_| self.save_meta());
+                if res.is_ok() {
+                    Some(PshResponse::Aliases(self.aliases()))
+                } else {
+                    Some(PshResponse::Failed("Failed to store password".to_string()))
+                }
+            }
+            PshRequest::RemoveAlias { alias } => {
+                let psh = self.psh.as_mut().unwrap();
+                let res = remove_alias(psh, &mut self.meta, alias).and_then(|_| self.save_meta());
+                if res.is_ok() {
+                    Some(PshResponse::Aliases(self.aliases()))
+                } else {
+                    Some(PshResponse::Failed("Failed to remove alias".to_string()))
+                }
+            }
+            PshRequest::RemoveAliases { aliases } => {
+                let psh = self.psh.as_mut().unwrap();
+                let res: anyhow::Result<()> = aliases.into_iter()
+                    .try_for_each(|alias| remove_alias(psh, &mut self.meta, alias))
+                    .and_then(|_| self.save_meta());
+                if res.is_ok() {
+                    Some(PshResponse::Aliases(self.aliases()))
+                } else {
+                    Some(PshResponse::Failed("Failed to remove aliases".to_string()))
+                }
+            }
+            PshRequest::UpdateAlias { alias, new } => {
+                let psh = self.psh.as_mut().unwrap();
+                let res = update_alias(psh, &mut self.meta, alias, new).and_then(|_| self.save_meta());
+                if res.is_ok() {
+                    Some(PshResponse::Aliases(self.aliases()))
+                } else {
+                    Some(PshResponse::Failed("Failed to update alias".to_string()))
+                }
+            }
+            PshRequest::ApplyImport { new, replace } => {
+                let psh = self.psh.as_mut().unwrap();
+                let mut passwords = std::mem::take(&mut self.imported_passwords);
+                let res = aliases::apply_import(psh, &mut self.meta, new, replace, &mut passwords)
+                    .and_then(|_| self.save_meta());
+                if res.is_ok() {
+                    Some(PshResponse::Aliases(self.aliases()))
+                } else {
+                    Some(PshResponse::Failed("Failed to import aliases".to_string()))
+                }
+            }
+            // Exports and imports involve files and are handled separately
+            PshRequest::Export { .. } | PshRequest::Import { .. } => unreachable!(),
+            PshRequest::PreviewMasterPasswordChange { old_password, new_password } => {
+                // Make sure that old master password is correct
+                if Psh::new(psh_secret(old_password), self.db.snapshot()).is_err() {
+                    return Some(PshResponse::MasterPasswordWrong);
+                }
+                let psh = self.psh.as_ref().unwrap();
+                let rekeyed = rekey(psh, new_password).and_then(|(new_psh, new_db)| {
+                    let changes = password_changes(psh, &new_psh, &self.meta)?;
+                    let meta = self.meta.seal(&new_psh)?;
+                    Ok((changes, new_psh, new_db, meta))
+                });
+                match rekeyed {
+                    Ok((changes, new_psh, new_db, meta)) => {
+                        self.rekeyed = Some((new_psh, new_db, meta));
+                        Some(PshResponse::MasterPasswordChangePreview(changes))
+                    }
+                    Err(e) => Some(PshResponse::Failed(format!("Failed to change master password: {}", e))),
+                }
+            }
+            PshRequest::ConfirmMasterPasswordChange => match self.rekeyed.take() {
+                Some((new_psh, new_db, meta)) => {
+                    // Persistent db gets replaced as a whole rather than record by record
+                    let records = new_db.records.borrow().clone();
+                    *new_db.journal.borrow_mut() = vec![StoreOp::Replace { records, meta }];
+                    self.db = new_db;
+                    self.psh = Some(new_psh);
+                    Some(PshResponse::MasterPasswordChanged)
+                }
+                None => Some(PshResponse::Failed("No master password change to confirm".to_string())),
+            },
+            PshRequest::CancelMasterPasswordChange => {
+                self.rekeyed = None;
+                None
+            }
+            PshRequest::Lock => {
+                self.psh = None;
+                self.meta = MetaStore::default();
+                self.rekeyed = None;
+                self.imported_passwords.clear();
+                None
+            }
+        }
+    }
+}
+
+impl PshWorker {
+    fn new() -> Self {
+        Self {
+            psh: None,
+            db: MemoryStore::default(),
+            meta_record: None,
+            meta: MetaStore::default(),
+            rekeyed: None,
+            imported_passwords: HashMap::new(),
+        }
+    }
+
+    fn received(&mut self, msg: WorkerInput, respond: impl Fn(WorkerOutput)) {
+        match msg {
+            WorkerInput::Load { records, meta } => {
+                self.db = MemoryStore::new(records);
+                self.meta_record = meta;
+            }
+            WorkerInput::Import { contents, passphrase } => {
+                let response = self.read_import(contents, passphrase);
+                respond(WorkerOutput::Response(response));
+            }
+            WorkerInput::Request(PshRequest::Export { passphrase }) => {
+                match self.export(passphrase) {
+                    Ok(contents) => respond(WorkerOutput::Export(contents)),
+                    Err(e) => {
+                        let response = PshResponse::Failed(format!("Failed to export database: {}", e));
+                        respond(WorkerOutput::Response(response));
+                    }
+                }
+            }
+            WorkerInput::Request(request) => {
+                let response = self.process(request);
+                let ops: Vec<StoreOp> = self.db.journal.borrow_mut().drain(..).collect();
+                if !ops.is_empty() {
+                    respond(WorkerOutput::Persist(ops));
+                }
+                if let Some(response) = response {
+                    respond(WorkerOutput::Response(response));
+                }
+            }
+        }
+    }
+
+    /// Serves [`WorkerInput`]s posted to the current Web Worker.
+    /// Must be called from the worker's entry point.
+    pub fn register() {
+        let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+        let mut worker = Self::new();
+        let worker_scope = scope.clone();
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+            let Ok(msg) = serde_wasm_bindgen::from_value(e.data()) else {
+                return;
+            };
+            worker.received(msg, |output| post_output(&worker_scope, output));
+        });
+        scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        on_message.forget();
+        // Messages posted before the handler was set are lost, so the main thread
+        // holds its input until it gets this one
+        post_output(&scope, WorkerOutput::Loaded);
+    }
+}
+
+fn post_output(scope: &DedicatedWorkerGlobalScope, output: WorkerOutput) {
+    let msg = serde_wasm_bindgen::to_value(&output).expect_throw("failed to encode worker output");
+    scope.post_message(&msg).expect_throw("failed to post worker output");
+}
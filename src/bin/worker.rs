@@ -0,0 +1,5 @@
+use psh_gui::backend::worker::PshWorker;
+
+fn main() {
+    PshWorker::register();
+}
@@ -0,0 +1,58 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::backend::is_tauri;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "clipboard"], js_name = writeText, catch)]
+    async fn tauri_write_text(text: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "clipboard"], js_name = readText, catch)]
+    async fn tauri_read_text() -> Result<JsValue, JsValue>;
+}
+
+fn web_clipboard() -> web_sys::Clipboard {
+    web_sys::window().unwrap()
+        .navigator()
+        .clipboard()
+}
+
+/// Puts text to system clipboard (native one in Tauri app).
+pub async fn write_text(text: &str) -> Result<(), JsValue> {
+    if is_tauri() {
+        tauri_write_text(text).await?;
+    } else {
+        JsFuture::from(web_clipboard().write_text(text)).await?;
+    }
+    Ok(())
+}
+
+async fn read_text() -> Result<String, JsValue> {
+    let text = if is_tauri() {
+        tauri_read_text().await?
+    } else {
+        JsFuture::from(web_clipboard().read_text()).await?
+    };
+    // Tauri returns null if clipboard holds no text
+    Ok(text.as_string().unwrap_or_default())
+}
+
+// Whether clipboard contents can be read at all (some browsers,
+// e.g. Firefox, don't let web pages read it)
+fn can_read() -> bool {
+    is_tauri() || js_sys::Reflect::has(&web_clipboard(), &JsValue::from_str("readText")).unwrap_or(false)
+}
+
+/// Clears clipboard if it still holds `text`. If clipboard can't be read
+/// (e.g. browser doesn't allow it, reading is denied or document isn't focused)
+/// it's left as is, since it may hold something copied since then.
+pub async fn clear_if_holds(text: &str) -> Result<(), JsValue> {
+    if !can_read() {
+        return Ok(());
+    }
+    match read_text().await {
+        Ok(current) if current == text => write_text("").await,
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+
+// Public suffix list rules (comments and blank lines aside)
+const PUBLIC_SUFFIX_LIST: &str = include_str!("domain/public-suffix-list.dat");
+
+thread_local! {
+    static SUFFIX_RULES: HashSet<&'static str> = PUBLIC_SUFFIX_LIST.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .collect();
+}
+
+// Takes host out of URL-like input, e.g. "https://user@www.example.com:443/login?x=1"
+fn host(input: &str) -> &str {
+    let rest = input.split_once("://").map_or(input, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = host.split(':').next().unwrap_or_default();
+    host.trim_end_matches('.')
+}
+
+fn is_valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+}
+
+// Number of trailing labels that make public suffix of domain
+fn suffix_len(labels: &[&str]) -> usize {
+    SUFFIX_RULES.with(|rules| {
+        for i in 0..labels.len() {
+            let candidate = labels[i..].join(".");
+            if rules.contains(format!("!{}", candidate).as_str()) {
+                return labels.len() - i - 1;
+            }
+            let wildcard = format!("*.{}", labels[i + 1..].join("."));
+            if rules.contains(candidate.as_str()) || (i + 1 < labels.len() && rules.contains(wildcard.as_str())) {
+                return labels.len() - i;
+            }
+        }
+        // Default rule: the last label is the suffix
+        1
+    })
+}
+
+/// Registrable domain of URL or host name ("https://www.github.com/login" is
+/// "github.com", "foo.co.uk" is "foo.co.uk"). Returns `None` if input
+/// doesn't look like domain name (e.g. it has no dots or it's an IP address).
+pub fn registrable_domain(input: &str) -> Option<String> {
+    let host = host(input.trim()).to_lowercase();
+    let labels: Vec<&str> = host.split('.').collect();
+    if labels.len() < 2 || !labels.iter().all(|label| is_valid_label(label)) {
+        return None;
+    }
+    if labels.iter().all(|label| label.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    let suffix_len = suffix_len(&labels);
+    if suffix_len >= labels.len() {
+        return None;
+    }
+    Some(labels[labels.len() - suffix_len - 1..].join("."))
+}
+
+/// Known aliases indexed by sites they stand for.
+#[derive(PartialEq)]
+pub struct Sites(Vec<(String, String)>);
+
+impl Sites {
+    /// Only aliases that are domain names stand for sites ("github" doesn't).
+    pub fn new(aliases: &[String]) -> Self {
+        Self(aliases.iter()
+            .filter_map(|alias| registrable_domain(alias).map(|domain| (domain, alias.clone())))
+            .collect())
+    }
+
+    /// Known alias that stands for the same site as `input`, e.g. "github.com"
+    /// for "https://www.github.com/login".
+    pub fn same_site(&self, input: &str) -> Option<&str> {
+        let domain = registrable_domain(input)?;
+        self.0.iter()
+            .find(|(alias_domain, _)| *alias_domain == domain)
+            .map(|(_, alias)| alias.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_registrable_domain() {
+        let cases = [
+            ("github.com", Some("github.com")),
+            ("https://www.github.com/login", Some("github.com")),
+            ("HTTPS://User@Mail.Example.COM:443/inbox?x=1#top", Some("example.com")),
+            ("login.example.com.", Some("example.com")),
+            ("foo.co.uk", Some("foo.co.uk")),
+            ("www.foo.co.uk", Some("foo.co.uk")),
+            ("example.unknowntld", Some("example.unknowntld")),
+            ("github", None),
+            ("co.uk", None),
+            ("192.168.0.1", None),
+            ("my bank.com", None),
+            ("-bad.com", None),
+            ("", None),
+        ];
+        for (input, domain) in cases {
+            assert_eq!(registrable_domain(input).as_deref(), domain, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn finds_alias_of_same_site() {
+        let sites = Sites::new(&[
+            "github".to_string(),
+            "gist.github.com".to_string(),
+            "foo.co.uk".to_string(),
+        ]);
+        let cases = [
+            ("https://www.github.com/login", Some("gist.github.com")),
+            ("github.com", Some("gist.github.com")),
+            ("github.io", None),
+            ("github", None),
+            ("foo.co.uk", Some("foo.co.uk")),
+            ("bar.co.uk", None),
+        ];
+        for (input, alias) in cases {
+            assert_eq!(sites.same_site(input), alias, "{:?}", input);
+        }
+    }
+}
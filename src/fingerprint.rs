@@ -0,0 +1,96 @@
+use crate::settings::{read_config, write_config};
+
+// Name of fingerprint key config
+const CONFIG_NAME: &str = "fingerprint-key";
+// Number of glyphs in fingerprint
+const GLYPH_COUNT: usize = 3;
+
+/// Shapes and colors that make up fingerprint glyphs.
+pub const SHAPES: [char; 4] = ['●', '▲', '■', '◆'];
+pub const COLORS: [&str; 4] = ["#e06c6c", "#e0c36c", "#6cbf75", "#6c9be0"];
+
+/// Glyph of a fingerprint as indices of its shape and color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glyph {
+    pub shape: usize,
+    pub color: usize,
+}
+
+// FNV-1a. Being fast is fine since fingerprint carries only a few bits of the input.
+fn hash(key: u64, text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in key.to_le_bytes().iter().chain(text.as_bytes()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    // Mix high bits into the low ones that glyphs are taken from
+    hash ^ (hash >> 29) ^ (hash >> 47)
+}
+
+/// Computes fingerprint of text. The fingerprint holds just 12 bits (4096 variants),
+/// so while typos are likely to change it, it tells next to nothing about the text.
+pub fn fingerprint(key: u64, text: &str) -> [Glyph; GLYPH_COUNT] {
+    let variants = (SHAPES.len() * COLORS.len()) as u64;
+    let mut bits = hash(key, text);
+    [(); GLYPH_COUNT].map(|_| {
+        let variant = (bits % variants) as usize;
+        bits /= variants;
+        Glyph { shape: variant / COLORS.len(), color: variant % COLORS.len() }
+    })
+}
+
+fn random_key() -> Option<u64> {
+    let mut bytes = [0u8; 8];
+    web_sys::window()?
+        .crypto().ok()?
+        .get_random_values_with_u8_array(&mut bytes).ok()?;
+    Some(u64::from_le_bytes(bytes))
+}
+
+/// Loads fingerprint key of this device, generating one on the first run.
+/// Key makes fingerprints differ between devices.
+pub async fn load_key() -> Option<u64> {
+    // JSON numbers may lose precision in JS, so the key is stored as string
+    let key = read_config(CONFIG_NAME).await
+        .and_then(|json| serde_json::from_str::<String>(&json).ok())
+        .and_then(|key| key.parse().ok());
+    if key.is_some() {
+        return key;
+    }
+    let key = random_key()?;
+    let json = serde_json::to_string(&key.to_string()).ok()?;
+    write_config(CONFIG_NAME, json).await.ok()?;
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn fingerprints_are_stable() {
+        assert_eq!(fingerprint(42, "master password"), fingerprint(42, "master password"));
+    }
+
+    #[test]
+    fn fingerprints_differ() {
+        // Typos and other devices' keys
+        assert_ne!(fingerprint(42, "master password"), fingerprint(42, "master passwork"));
+        assert_ne!(fingerprint(42, "master password"), fingerprint(42, "Master password"));
+        assert_ne!(fingerprint(42, "master password"), fingerprint(43, "master password"));
+    }
+
+    #[test]
+    fn uses_every_glyph() {
+        let mut glyphs = HashSet::new();
+        for i in 0..1000 {
+            for glyph in fingerprint(7, &i.to_string()) {
+                assert!(glyph.shape < SHAPES.len() && glyph.color < COLORS.len());
+                glyphs.insert((glyph.shape, glyph.color));
+            }
+        }
+        assert_eq!(glyphs.len(), SHAPES.len() * COLORS.len());
+    }
+}
@@ -0,0 +1,152 @@
+// Scores of fuzzy matching (similar to the ones of fzf)
+const SCORE_MATCH: i32 = 16;
+const SCORE_GAP_START: i32 = -3;
+const SCORE_GAP_EXTENSION: i32 = -1;
+// Bonus for matching at the very start of text
+const BONUS_PREFIX: i32 = 16;
+// Bonus for matching at the start of a word, e.g. "b" in "my-bank" or "myBank"
+const BONUS_BOUNDARY: i32 = 8;
+const BONUS_CONSECUTIVE: i32 = 4;
+
+/// Text that matched the pattern along with positions (in chars) of matched characters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match {
+    pub text: String,
+    pub positions: Vec<usize>,
+    pub score: i32,
+}
+
+fn bonus(chars: &[char], i: usize) -> i32 {
+    if i == 0 {
+        return BONUS_PREFIX + BONUS_BOUNDARY;
+    }
+    let (prev, c) = (chars[i - 1], chars[i]);
+    if (!prev.is_alphanumeric() && c.is_alphanumeric()) || (prev.is_lowercase() && c.is_uppercase()) {
+        BONUS_BOUNDARY
+    } else {
+        0
+    }
+}
+
+// Case folding that keeps one char per char, so that positions in folded
+// text are the ones in original text
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn gap_penalty(gap: usize) -> i32 {
+    if gap == 0 {
+        0
+    } else {
+        SCORE_GAP_START + SCORE_GAP_EXTENSION * (gap as i32 - 1)
+    }
+}
+
+/// Matches case insensitive `pattern` against `text` as a subsequence picking
+/// the best scored positions. Returns `None` if some pattern characters are missing.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<Match> {
+    let pattern: Vec<char> = pattern.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(fold)
+        .collect();
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().copied().map(fold).collect();
+    if pattern.is_empty() {
+        return Some(Match { text: text.to_string(), positions: Vec::new(), score: 0 });
+    }
+
+    // Best score of matching pattern[..=i] with pattern[i] at position j
+    // along with position of pattern[i - 1] it was reached from
+    let mut scores: Vec<Vec<Option<(i32, usize)>>> = vec![vec![None; chars.len()]; pattern.len()];
+    for (i, pc) in pattern.iter().enumerate() {
+        for j in (0..chars.len()).filter(|j| lower[*j] == *pc) {
+            let own = SCORE_MATCH + bonus(&chars, j);
+            scores[i][j] = if i == 0 {
+                Some((own, 0))
+            } else {
+                (0..j)
+                    .filter_map(|k| scores[i - 1][k].map(|(score, _)| (score, k)))
+                    .map(|(score, k)| {
+                        let consecutive = if k + 1 == j { BONUS_CONSECUTIVE } else { 0 };
+                        (score + own + consecutive + gap_penalty(j - k - 1), k)
+                    })
+                    .max_by_key(|(score, _)| *score)
+            };
+        }
+    }
+
+    let last = pattern.len() - 1;
+    let (mut j, score) = (0..chars.len())
+        .filter_map(|j| scores[last][j].map(|(score, _)| (j, score)))
+        .max_by_key(|(_, score)| *score)?;
+    let mut positions = vec![0; pattern.len()];
+    for i in (0..pattern.len()).rev() {
+        positions[i] = j;
+        j = scores[i][j].unwrap().1;
+    }
+    Some(Match { text: text.to_string(), positions, score })
+}
+
+/// Texts that match `pattern` ordered from the best match (equally scored
+/// ones from the shortest). Empty pattern matches all the texts in their order.
+pub fn rank(pattern: &str, texts: &[String]) -> Vec<Match> {
+    let mut matches: Vec<Match> = texts.iter()
+        .filter_map(|text| fuzzy_match(pattern, text))
+        .collect();
+    if !pattern.trim().is_empty() {
+        matches.sort_by_key(|m| (-m.score, m.text.chars().count()));
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|text| text.to_string()).collect()
+    }
+
+    #[test]
+    fn highlights_best_positions() {
+        let cases: &[(&str, &str, Option<&[usize]>)] = &[
+            ("gh", "github", Some(&[0, 3])),
+            ("hub", "github", Some(&[3, 4, 5])),
+            ("bank", "my-bank", Some(&[3, 4, 5, 6])),
+            ("mb", "myBank", Some(&[0, 2])),
+            ("GIT", "github", Some(&[0, 1, 2])),
+            ("git hub", "github", Some(&[0, 1, 2, 3, 4, 5])),
+            ("İs", "İstanbul", Some(&[0, 1])),
+            ("ß", "Straße", Some(&[4])),
+            ("xyz", "github", None),
+            ("hg", "github", None),
+        ];
+        for (pattern, text, positions) in cases {
+            let found = fuzzy_match(pattern, text).map(|m| m.positions);
+            assert_eq!(found.as_deref(), *positions, "{:?} in {:?}", pattern, text);
+        }
+    }
+
+    #[test]
+    fn ranks_prefix_and_boundary_matches_first() {
+        let cases: &[(&str, &[&str], &[&str])] = &[
+            ("gh", &["ghost.io", "github.com", "laughing"], &["ghost.io", "github.com", "laughing"]),
+            ("bank", &["bankofamerica", "my-bank", "mybankers"], &["bankofamerica", "my-bank", "mybankers"]),
+            ("mail", &["gmail.com", "mail.google.com", "mail.com"], &["mail.com", "mail.google.com", "gmail.com"]),
+            ("x", &["github", "gitlab"], &[]),
+        ];
+        for (pattern, candidates, expected) in cases {
+            let ranked: Vec<String> = rank(pattern, &texts(candidates)).into_iter()
+                .map(|m| m.text)
+                .collect();
+            assert_eq!(ranked, texts(expected), "{:?}", pattern);
+        }
+    }
+
+    #[test]
+    fn empty_pattern_keeps_order() {
+        let candidates = texts(&["b", "a", "c"]);
+        let ranked: Vec<String> = rank(" ", &candidates).into_iter().map(|m| m.text).collect();
+        assert_eq!(ranked, candidates);
+    }
+}
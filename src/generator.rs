@@ -0,0 +1,118 @@
+// Characters of generated passwords by class (similar looking ones are left out)
+const LOWERCASE: &str = "abcdefghijkmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHJKLMNPQRSTUVWXYZ";
+const DIGITS: &str = "23456789";
+const SYMBOLS: &str = "!#$%&*+-=?@^_~";
+
+/// Lengths of generated passwords user can choose from.
+pub const RANDOM_LENGTHS: [usize; 5] = [12, 16, 20, 24, 32];
+
+fn random_u32s(count: usize) -> Option<Vec<u32>> {
+    let mut bytes = vec![0u8; count * 4];
+    web_sys::window()?
+        .crypto().ok()?
+        .get_random_values_with_u8_array(&mut bytes).ok()?;
+    Some(bytes.chunks(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+}
+
+// Picks random element of `chars` without modulo bias
+fn random_char(chars: &[char], next_u32: &mut impl FnMut() -> Option<u32>) -> Option<char> {
+    let n = chars.len() as u32;
+    let zone = u32::MAX - u32::MAX % n;
+    loop {
+        let r = next_u32()?;
+        if r < zone {
+            return Some(chars[(r % n) as usize]);
+        }
+    }
+}
+
+/// Generates random password of given length that has every character class.
+/// Returns `None` if password is too short to have every class or browser has
+/// no cryptographically secure random generator.
+pub fn random_password(length: usize, use_symbols: bool) -> Option<String> {
+    // Random numbers are drawn in batches enough for a whole password
+    // (unless some are rejected), not one by one
+    let mut batch = Vec::new();
+    generate(length, use_symbols, || {
+        if batch.is_empty() {
+            batch = random_u32s(length)?;
+        }
+        batch.pop()
+    })
+}
+
+// Generates password taking random numbers from `next_u32`
+fn generate(length: usize, use_symbols: bool, mut next_u32: impl FnMut() -> Option<u32>) -> Option<String> {
+    let mut classes = vec![LOWERCASE, UPPERCASE, DIGITS];
+    if use_symbols {
+        classes.push(SYMBOLS);
+    }
+    if length < classes.len() {
+        return None;
+    }
+    let chars: Vec<char> = classes.concat().chars().collect();
+    loop {
+        let password = (0..length)
+            .map(|_| random_char(&chars, &mut next_u32))
+            .collect::<Option<String>>()?;
+        // Passwords lacking some class are rejected rather than patched up
+        // to keep all the positions uniformly random
+        if classes.iter().all(|class| password.chars().any(|c| class.contains(c))) {
+            return Some(password);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Deterministic stand-in for Web Crypto (xorshift)
+    fn pseudo_random(mut state: u32) -> impl FnMut() -> Option<u32> {
+        move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            Some(state)
+        }
+    }
+
+    #[test]
+    fn generates_passwords_with_every_class() {
+        for seed in 1..100 {
+            for (length, use_symbols) in [(12, true), (32, true), (4, true), (3, false), (16, false)] {
+                let password = generate(length, use_symbols, pseudo_random(seed)).unwrap();
+                assert_eq!(password.chars().count(), length);
+                let mut classes = vec![LOWERCASE, UPPERCASE, DIGITS];
+                if use_symbols {
+                    classes.push(SYMBOLS);
+                }
+                for class in &classes {
+                    assert!(password.chars().any(|c| class.contains(c)), "{:?}", password);
+                }
+                assert!(password.chars().all(|c| classes.iter().any(|class| class.contains(c))), "{:?}", password);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_biased_numbers() {
+        let mut numbers = vec![Some(u32::MAX), Some(3)].into_iter();
+        let chars: Vec<char> = DIGITS.chars().collect();
+        // u32::MAX falls into the incomplete last round of `chars`
+        assert_eq!(random_char(&chars, &mut || numbers.next().flatten()), Some('5'));
+    }
+
+    #[test]
+    fn refuses_too_short_passwords() {
+        assert_eq!(generate(3, true, pseudo_random(1)), None);
+        assert_eq!(generate(2, false, pseudo_random(1)), None);
+        assert_eq!(generate(0, false, pseudo_random(1)), None);
+    }
+
+    #[test]
+    fn fails_without_random_generator() {
+        assert_eq!(generate(12, true, || None), None);
+    }
+}
@@ -1,6 +1,18 @@
 use wasm_bindgen::prelude::wasm_bindgen;
 
+mod alias_names;
 mod app;
+pub mod backend;
+mod clipboard;
+mod domain;
+mod fingerprint;
+mod fuzzy;
+mod generator;
+mod lockout;
+mod secret;
+mod settings;
+mod site_rules;
+mod strength;
 
 use app::App;
 
@@ -8,3 +20,10 @@ use app::App;
 pub fn main() {
     yew::Renderer::<App>::new().render();
 }
+
+// Entry point of Psh worker module (see `src/bin/worker.rs` for Trunk builds)
+#[cfg(feature = "worker")]
+#[wasm_bindgen(start)]
+pub fn start_worker() {
+    backend::worker::PshWorker::register();
+}
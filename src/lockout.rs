@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{read_config, write_config};
+
+// Name of lockout config
+const CONFIG_NAME: &str = "lockout";
+// Failed unlock attempts allowed without delay
+const FREE_ATTEMPTS: u32 = 3;
+const MAX_DELAY_SEC: u32 = 900;
+
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+// Delay after given number of failed attempts in a row: 2, 4, 8... seconds
+fn delay_sec(failures: u32) -> u32 {
+    if failures < FREE_ATTEMPTS {
+        0
+    } else {
+        2u32.saturating_pow(failures - FREE_ATTEMPTS + 1).min(MAX_DELAY_SEC)
+    }
+}
+
+/// Failed unlock attempts. Persisted so that restarting the app doesn't reset back-off.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Lockout {
+    // Failed unlock attempts in a row
+    pub failures: u32,
+    // Time (in ms since Unix epoch) before which unlock isn't allowed
+    pub blocked_until: f64,
+}
+
+impl Lockout {
+    pub fn record_failure(&mut self) {
+        self.failures += 1;
+        self.blocked_until = now_ms() + f64::from(delay_sec(self.failures)) * 1000.0;
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Seconds left before next unlock attempt is allowed.
+    pub fn seconds_left(&self) -> u32 {
+        let left = ((self.blocked_until - now_ms()) / 1000.0).ceil();
+        // Never trust a clock that was turned back further than the longest delay
+        left.clamp(0.0, f64::from(MAX_DELAY_SEC)) as u32
+    }
+}
+
+/// Loads lockout state from config file (Tauri app) or Web Storage.
+pub async fn load() -> Lockout {
+    read_config(CONFIG_NAME).await
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Saves lockout state to config file (Tauri app) or Web Storage.
+pub async fn save(lockout: &Lockout) -> Result<(), String> {
+    let json = serde_json::to_string(lockout).map_err(|e| e.to_string())?;
+    write_config(CONFIG_NAME, json).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_after_free_attempts() {
+        let cases = [(0, 0), (1, 0), (2, 0), (3, 2), (4, 4), (5, 8), (11, 512), (12, 900), (40, 900), (u32::MAX, 900)];
+        for (failures, delay) in cases {
+            assert_eq!(delay_sec(failures), delay, "{}", failures);
+        }
+    }
+}
@@ -1,4 +1,16 @@
+mod alias_names;
 mod app;
+mod backend;
+mod clipboard;
+mod domain;
+mod fingerprint;
+mod fuzzy;
+mod generator;
+mod lockout;
+mod secret;
+mod settings;
+mod site_rules;
+mod strength;
 
 use app::App;
 
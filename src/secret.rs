@@ -0,0 +1,129 @@
+use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use zeroize::Zeroizing;
+
+// Character that masks of hidden secrets consist of
+const MASK_CHAR: char = '•';
+
+// Length of mask shown in place of a secret. It doesn't depend on the secret,
+// so that secret's length can't be told from the mask.
+const MASK_LEN: usize = 8;
+
+/// Mask shown in place of a hidden secret (nothing for an empty one).
+pub fn mask(secret: &str) -> String {
+    if secret.is_empty() {
+        String::new()
+    } else {
+        MASK_CHAR.to_string().repeat(MASK_LEN)
+    }
+}
+
+/// Immutable string for sensitive data that is wiped from memory once dropped.
+///
+/// Clones share the same buffer, so passing a secret around (e.g. as component
+/// property) doesn't leave copies of it behind. The buffer gets zeroized when
+/// the last clone is dropped or cleared.
+#[derive(Clone, Default)]
+pub struct SecretString(Rc<Zeroizing<String>>);
+
+impl SecretString {
+    pub fn new(string: String) -> Self {
+        Self(Rc::new(Zeroizing::new(string)))
+    }
+
+    /// Copy of the secret to be sent to Psh backend (wiped once sent).
+    pub fn expose(&self) -> Zeroizing<String> {
+        Zeroizing::new(self.to_string())
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Applies user input to the secret. Edited secret is written to a new buffer
+    /// of exact size, so the old one is zeroized rather than left behind by reallocation.
+    pub fn edit(&mut self, edit: SecretEdit) {
+        let string = match edit {
+            SecretEdit::Insert(input) => {
+                let mut string = String::with_capacity(self.len() + input.len());
+                string.push_str(self);
+                string.push_str(&input);
+                string
+            }
+            SecretEdit::DeleteBackward => {
+                let mut string = String::with_capacity(self.len());
+                string.push_str(self);
+                string.pop();
+                string
+            }
+            SecretEdit::Clear => String::new(),
+        };
+        *self = Self::new(string);
+    }
+}
+
+/// Change of a secret made by user. Secrets are only edited at the end.
+#[derive(Debug, PartialEq)]
+pub enum SecretEdit {
+    Insert(SecretString),
+    DeleteBackward,
+    Clear,
+}
+
+impl From<Zeroizing<String>> for SecretString {
+    fn from(string: Zeroizing<String>) -> Self {
+        Self(Rc::new(string))
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(string: String) -> Self {
+        Self::new(string)
+    }
+}
+
+impl Deref for SecretString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl PartialEq for SecretString {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0) || self.0.as_str() == other.0.as_str()
+    }
+}
+
+// Never print secrets accidentally
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretString(***)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_secret() {
+        let mut secret = SecretString::default();
+        secret.edit(SecretEdit::Insert("pässword".to_string().into()));
+        secret.edit(SecretEdit::DeleteBackward);
+        assert_eq!(&*secret, "pässwor");
+        secret.edit(SecretEdit::Insert("d".to_string().into()));
+        assert_eq!(&*secret, "pässword");
+        secret.edit(SecretEdit::Clear);
+        assert_eq!(&*secret, "");
+    }
+
+    #[test]
+    fn masks_secrets_of_any_length_alike() {
+        assert_eq!(mask(""), "");
+        assert_eq!(mask("a"), mask("a much longer secret"));
+    }
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::backend::{self, is_tauri};
+
+/// Version of [`Settings`] model. Bump it when the meaning of existing fields changes.
+pub const SETTINGS_VERSION: u32 = 1;
+
+// Name of settings config
+const CONFIG_NAME: &str = "settings";
+
+// Ranges of valid setting values
+const CHARSET_COUNT: usize = 3;
+const ALIAS_HANDLE_COUNT: usize = 2;
+const MAX_DELAY_SEC: u32 = 3600;
+const MIN_WIPE_FAILURES: u32 = 5;
+const MAX_MP_SCORE: u8 = 4;
+
+/// User settings. Missing fields (e.g. ones added in later versions) take default values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    // Visibility of alias options
+    pub options_visible: bool,
+    // Visibility of built-in keyboard
+    pub kb_visible: bool,
+    // Charset id for new aliases
+    pub charset: usize,
+    // Alias handle id for new aliases (0 is "Store", 1 is "Don't store")
+    pub alias_handle: usize,
+    // Lock after this many seconds without user input
+    pub idle_timeout_sec: Option<u32>,
+    // Lock this many seconds after app goes to background (0 means immediately)
+    pub pause_timeout_sec: Option<u32>,
+    // Lock when Tauri app window gets minimized
+    pub lock_on_minimize: bool,
+    // Delay before copied password is cleared from clipboard
+    pub clipboard_clear_sec: u32,
+    // Clear copied password from clipboard when app gets locked
+    pub clear_clipboard_on_lock: bool,
+    // Delay before derived password is wiped from screen
+    pub password_expiry_sec: u32,
+    // Wipe psh database after this many failed unlock attempts in a row
+    pub wipe_after_failures: Option<u32>,
+    // Keep unencrypted list of alias names to be saved after master password is lost
+    pub keep_alias_names: bool,
+    // Minimum strength score (0 to 4) of master password set on first run
+    pub min_mp_score: u8,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            options_visible: false,
+            kb_visible: true,
+            charset: 0,
+            alias_handle: 0,
+            idle_timeout_sec: Some(300),
+            pause_timeout_sec: Some(20),
+            lock_on_minimize: true,
+            clipboard_clear_sec: 30,
+            clear_clipboard_on_lock: true,
+            password_expiry_sec: 30,
+            wipe_after_failures: None,
+            keep_alias_names: false,
+            min_mp_score: 3,
+        }
+    }
+}
+
+impl Settings {
+    fn from_json(json: &str) -> Self {
+        match serde_json::from_str::<Settings>(json) {
+            // Settings of future versions can't be trusted to mean the same
+            Ok(settings) if settings.version <= SETTINGS_VERSION => {
+                Settings { version: SETTINGS_VERSION, ..settings }.validated()
+            }
+            _ => Settings::default(),
+        }
+    }
+
+    // Replaces values out of range (e.g. edited by hand) with default ones
+    fn validated(mut self) -> Self {
+        let default = Settings::default();
+        if self.charset >= CHARSET_COUNT {
+            self.charset = default.charset;
+        }
+        if self.alias_handle >= ALIAS_HANDLE_COUNT {
+            self.alias_handle = default.alias_handle;
+        }
+        if self.idle_timeout_sec == Some(0) {
+            self.idle_timeout_sec = default.idle_timeout_sec;
+        }
+        if !(1..=MAX_DELAY_SEC).contains(&self.clipboard_clear_sec) {
+            self.clipboard_clear_sec = default.clipboard_clear_sec;
+        }
+        if !(1..=MAX_DELAY_SEC).contains(&self.password_expiry_sec) {
+            self.password_expiry_sec = default.password_expiry_sec;
+        }
+        // Database must not get wiped sooner than user could have chosen
+        if self.wipe_after_failures.is_some_and(|n| n < MIN_WIPE_FAILURES) {
+            self.wipe_after_failures = default.wipe_after_failures;
+        }
+        if self.min_mp_score > MAX_MP_SCORE {
+            self.min_mp_score = default.min_mp_score;
+        }
+        self
+    }
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+// Web Storage key of config with given name
+fn storage_key(name: &str) -> String {
+    format!("psh-gui-{}", name)
+}
+
+/// Reads config with given name from config file (Tauri app) or Web Storage.
+pub async fn read_config(name: &str) -> Option<String> {
+    if is_tauri() {
+        backend::tauri::read_config(name).await.ok().flatten()
+    } else {
+        local_storage()?.get_item(&storage_key(name)).ok().flatten()
+    }
+}
+
+/// Writes config with given name to config file (Tauri app) or Web Storage.
+pub async fn write_config(name: &str, contents: String) -> Result<(), String> {
+    if is_tauri() {
+        backend::tauri::write_config(name, contents).await
+    } else {
+        local_storage()
+            .ok_or_else(|| "Web Storage is not available".to_string())?
+            .set_item(&storage_key(name), &contents)
+            .map_err(|e| format!("{:?}", e))
+    }
+}
+
+/// Loads settings from config file (Tauri app) or Web Storage.
+pub async fn load() -> Settings {
+    read_config(CONFIG_NAME).await
+        .map(|json| Settings::from_json(&json))
+        .unwrap_or_default()
+}
+
+/// Saves settings to config file (Tauri app) or Web Storage.
+pub async fn save(settings: &Settings) -> Result<(), String> {
+    let json = serde_json::to_string(settings).map_err(|e| e.to_string())?;
+    write_config(CONFIG_NAME, json).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_settings_of_earlier_versions() {
+        // Settings saved before versioning lack both version and later fields
+        let settings = Settings::from_json(r#"{"charset": 2, "kb_visible": false}"#);
+        assert_eq!(settings, Settings { charset: 2, kb_visible: false, ..Settings::default() });
+        assert_eq!(settings.version, SETTINGS_VERSION);
+    }
+
+    #[test]
+    fn ignores_settings_of_later_versions() {
+        let json = format!(r#"{{"version": {}, "charset": 2}}"#, SETTINGS_VERSION + 1);
+        assert_eq!(Settings::from_json(&json), Settings::default());
+    }
+
+    #[test]
+    fn ignores_malformed_settings() {
+        assert_eq!(Settings::from_json("not json"), Settings::default());
+        assert_eq!(Settings::from_json(r#"{"charset": "two"}"#), Settings::default());
+    }
+
+    #[test]
+    fn replaces_invalid_values() {
+        let json = r#"{"charset": 3, "alias_handle": 7, "idle_timeout_sec": 0, "clipboard_clear_sec": 0,
+            "password_expiry_sec": 100000, "wipe_after_failures": 1, "min_mp_score": 9, "kb_visible": false}"#;
+        assert_eq!(Settings::from_json(json), Settings { kb_visible: false, ..Settings::default() });
+        let json = r#"{"charset": 2, "alias_handle": 1, "idle_timeout_sec": null, "wipe_after_failures": 5}"#;
+        let settings = Settings::from_json(json);
+        assert_eq!(settings, Settings {
+            charset: 2,
+            alias_handle: 1,
+            idle_timeout_sec: None,
+            wipe_after_failures: Some(5),
+            ..Settings::default()
+        });
+    }
+
+    #[test]
+    fn round_trips_settings() {
+        let settings = Settings { charset: 1, idle_timeout_sec: None, ..Settings::default() };
+        assert_eq!(Settings::from_json(&serde_json::to_string(&settings).unwrap()), settings);
+    }
+}
@@ -0,0 +1,183 @@
+use serde::Deserialize;
+
+use psh::CharSet;
+
+use crate::domain::registrable_domain;
+
+// Password rules of sites that reject some passwords (max length, symbols, etc).
+// Rules are taken from Apple's password manager resources
+// (https://github.com/apple/password-manager-resources, quirks/password-rules.json)
+const SITE_RULES: &str = include_str!("site_rules/site-rules.json");
+
+thread_local! {
+    static RULES: Vec<SiteRule> = serde_json::from_str(SITE_RULES).expect("Malformed site rules");
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Lower => "lowercase",
+            Self::Upper => "uppercase",
+            Self::Digit => "digits",
+            Self::Symbol => "symbols",
+        }
+    }
+}
+
+/// Password constraints of a site.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct SiteRule {
+    pub domain: String,
+    #[serde(default)]
+    pub min: Option<usize>,
+    #[serde(default)]
+    pub max: Option<usize>,
+    // Character classes password must have
+    #[serde(default)]
+    pub require: Vec<CharClass>,
+    // Symbols site accepts (any if not set)
+    #[serde(default)]
+    pub symbols: Option<String>,
+}
+
+impl SiteRule {
+    fn matches(&self, alias: &str) -> bool {
+        alias == self.domain || registrable_domain(alias).is_some_and(|domain| domain == self.domain)
+    }
+
+    /// Charset that meets the rule best. Psh charsets can't be limited to given
+    /// symbols, so sites that don't accept some of them get symbols left out
+    /// unless they require ones.
+    pub fn charset(&self) -> CharSet {
+        if self.require.contains(&CharClass::Symbol) || (self.symbols.is_none() && !self.require.is_empty()) {
+            CharSet::RequireAll
+        } else if self.symbols.is_some() {
+            CharSet::Reduced
+        } else {
+            CharSet::Standard
+        }
+    }
+
+    /// The longest of password `lengths` site accepts (`None` is full length).
+    pub fn length(&self, lengths: &[Option<usize>]) -> Option<usize> {
+        self.max?;
+        lengths.iter()
+            .flatten()
+            .filter(|length| self.min.is_none_or(|min| **length >= min))
+            .filter(|length| self.max.is_none_or(|max| **length <= max))
+            .max()
+            .copied()
+    }
+
+    /// Explains what the rule is about, e.g. "8-16 characters, requires digits, no symbols".
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => parts.push(format!("{}-{} characters", min, max)),
+            (Some(min), None) => parts.push(format!("at least {} characters", min)),
+            (None, Some(max)) => parts.push(format!("at most {} characters", max)),
+            (None, None) => {}
+        }
+        if !self.require.is_empty() {
+            let classes: Vec<&str> = self.require.iter().map(|class| class.label()).collect();
+            parts.push(format!("requires {}", classes.join(", ")));
+        }
+        match self.symbols.as_deref() {
+            Some("") => parts.push("no symbols".to_string()),
+            Some(symbols) => parts.push(format!("only {} symbols", symbols)),
+            None => {}
+        }
+        parts.join(", ")
+    }
+
+    /// Whether passwords of rule's charset may still be rejected by site. Only
+    /// `RequireAll` guarantees required classes, but it may use symbols site rejects.
+    pub fn may_fail(&self) -> bool {
+        match self.charset() {
+            CharSet::RequireAll => self.symbols.is_some(),
+            _ => !self.require.is_empty(),
+        }
+    }
+}
+
+/// Finds password rule of site that alias stands for ("example.com",
+/// "login.example.com" or "https://www.example.com/login").
+pub fn find(alias: &str) -> Option<SiteRule> {
+    let alias = alias.trim().to_lowercase();
+    RULES.with(|rules| rules.iter().find(|rule| rule.matches(&alias)).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(json: &str) -> SiteRule {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn finds_rule_by_domain() {
+        let cases = [
+            ("delta.com", Some("delta.com")),
+            ("www.delta.com", Some("delta.com")),
+            ("https://www.Delta.com/login", Some("delta.com")),
+            ("delta", None),
+            ("deltaairlines.com", None),
+            ("ing", None),
+            ("aa", None),
+            ("apple", None),
+        ];
+        for (alias, domain) in cases {
+            assert_eq!(find(alias).map(|rule| rule.domain).as_deref(), domain, "{:?}", alias);
+        }
+    }
+
+    #[test]
+    fn picks_charset() {
+        let cases = [
+            (r#"{ "domain": "a.com" }"#, CharSet::Standard, false),
+            (r#"{ "domain": "a.com", "max": 20 }"#, CharSet::Standard, false),
+            (r#"{ "domain": "a.com", "require": ["digit"] }"#, CharSet::RequireAll, false),
+            (r#"{ "domain": "a.com", "symbols": "" }"#, CharSet::Reduced, false),
+            (r#"{ "domain": "a.com", "require": ["upper", "digit"], "symbols": "" }"#, CharSet::Reduced, true),
+            (r#"{ "domain": "a.com", "require": ["digit"], "symbols": "@$" }"#, CharSet::Reduced, true),
+            (r#"{ "domain": "a.com", "require": ["symbol"], "symbols": "@$" }"#, CharSet::RequireAll, true),
+            (r#"{ "domain": "a.com", "require": ["lower", "symbol"] }"#, CharSet::RequireAll, false),
+        ];
+        for (json, charset, may_fail) in cases {
+            let rule = rule(json);
+            assert_eq!(rule.charset(), charset, "{}", json);
+            assert_eq!(rule.may_fail(), may_fail, "{}", json);
+        }
+    }
+
+    #[test]
+    fn picks_longest_accepted_length() {
+        let lengths = [None, Some(8), Some(12), Some(16), Some(20)];
+        let cases = [
+            (r#"{ "domain": "a.com" }"#, None),
+            (r#"{ "domain": "a.com", "min": 8 }"#, None),
+            (r#"{ "domain": "a.com", "max": 16 }"#, Some(16)),
+            (r#"{ "domain": "a.com", "min": 6, "max": 8 }"#, Some(8)),
+            (r#"{ "domain": "a.com", "min": 13, "max": 15 }"#, None),
+        ];
+        for (json, length) in cases {
+            assert_eq!(rule(json).length(&lengths), length, "{}", json);
+        }
+    }
+
+    #[test]
+    fn describes_rule() {
+        let rule = rule(r#"{ "domain": "a.com", "min": 8, "max": 16, "require": ["upper", "digit"], "symbols": "" }"#);
+        assert_eq!(rule.describe(), "8-16 characters, requires uppercase, digits, no symbols");
+    }
+}
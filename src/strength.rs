@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+// Common passwords ordered by popularity (most common first)
+const COMMON_PASSWORDS: &str = include_str!("strength/common-passwords.txt");
+// Shortest substring that is looked up in common passwords
+const MIN_WORD_LEN: usize = 4;
+// Guesses per character not matched by any pattern: counting every printable
+// character would rate random five-character passwords as good ones
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+
+/// Names of password strength scores.
+pub const SCORE_LABELS: [&str; 5] = ["Very weak", "Weak", "Fair", "Good", "Strong"];
+
+/// Choices of minimum strength required from new master password.
+pub const MIN_SCORES: [(u8, &str); 5] = [
+    (0, "Any"),
+    (1, SCORE_LABELS[1]),
+    (2, SCORE_LABELS[2]),
+    (3, SCORE_LABELS[3]),
+    (4, SCORE_LABELS[4]),
+];
+
+thread_local! {
+    // Common password ranks by password
+    static COMMON_RANKS: HashMap<&'static str, usize> = COMMON_PASSWORDS.lines()
+        .enumerate()
+        .map(|(rank, password)| (password, rank))
+        .collect();
+}
+
+/// Offline estimate of password strength. It's a basic check that only knows
+/// a short list of common passwords and simple patterns, so passwords it rates
+/// high aren't necessarily strong.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Strength {
+    // From 0 (too guessable) to 4 (very unguessable)
+    pub score: u8,
+    // What makes password weak
+    pub warning: Option<&'static str>,
+}
+
+// Common passwords are looked up both as typed and with substitutions undone
+fn common_rank(word: &str) -> Option<usize> {
+    COMMON_RANKS.with(|ranks| {
+        ranks.get(word.to_lowercase().as_str())
+            .or_else(|| ranks.get(word.chars().map(unleet).collect::<String>().as_str()))
+            .copied()
+    })
+}
+
+// Undoes common character substitutions
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' | '!' => 'i',
+        '0' => 'o',
+        '$' | '5' => 's',
+        '7' => 't',
+        c => c.to_ascii_lowercase(),
+    }
+}
+
+// Finds the longest common password among substrings not covered yet.
+// Returns its position along with popularity rank.
+fn longest_common(chars: &[char], covered: &[bool]) -> Option<(usize, usize, usize)> {
+    for len in (MIN_WORD_LEN..=chars.len()).rev() {
+        for start in 0..=chars.len() - len {
+            let end = start + len;
+            if covered[start..end].iter().any(|c| *c) {
+                continue;
+            }
+            let word: String = chars[start..end].iter().collect();
+            if let Some(rank) = common_rank(&word) {
+                return Some((start, end, rank));
+            }
+        }
+    }
+    None
+}
+
+/// Estimates password strength: common passwords (of a short built-in list),
+/// repeats and sequences are counted as single guesses while other characters
+/// as random ones.
+pub fn estimate(password: &str) -> Strength {
+    let chars: Vec<char> = password.chars().collect();
+    let char_bits = BRUTEFORCE_CARDINALITY.log2();
+    let mut bits = 0.0;
+    let mut covered = vec![false; chars.len()];
+
+    let mut has_common = false;
+    while let Some((start, end, rank)) = longest_common(&chars, &covered) {
+        let word = &chars[start..end];
+        // Attacker has to try upper case and substituted variants as well
+        let variations = if word.iter().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()) {
+            0.0
+        } else {
+            1.0
+        };
+        bits += ((rank + 2) as f64).log2() + variations;
+        covered[start..end].fill(true);
+        has_common = true;
+    }
+
+    let mut has_sequences = false;
+    for i in (0..chars.len()).filter(|i| !covered[*i]) {
+        // Repeated characters and sequences like "abc" are next to free for attacker
+        let continues_sequence = i > 0 && !covered[i - 1] && {
+            let step = chars[i] as i64 - chars[i - 1] as i64;
+            step.abs() <= 1
+        };
+        if continues_sequence {
+            bits += 1.0;
+            has_sequences = true;
+        } else {
+            bits += char_bits;
+        }
+    }
+
+    let guesses_log10 = bits * 2f64.log10();
+    let score = match guesses_log10 {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    };
+    let warning = if password.is_empty() {
+        None
+    } else if common_rank(password).is_some() {
+        Some("This is one of the most common passwords")
+    } else if has_common && score < 3 {
+        Some("Common words and passwords are easy to guess")
+    } else if has_sequences && score < 3 {
+        Some("Repeats and sequences like \"aaa\" or \"abc\" are easy to guess")
+    } else if score < 3 {
+        Some("Add more words or characters")
+    } else {
+        None
+    };
+    Strength { score, warning }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_passwords() {
+        let cases = [
+            ("", 0),
+            ("password", 0),
+            ("P@ssw0rd", 0),
+            ("qwerty123", 0),
+            ("dragonmonkey", 0),
+            ("ggggggggggg", 1),
+            ("ghijklmnopq", 1),
+            ("k7#Qz", 1),
+            ("k7#Qzw9", 2),
+            ("x9$Lq2!vR7zP", 4),
+            ("correct horse battery staple", 4),
+        ];
+        for (password, score) in cases {
+            assert_eq!(estimate(password).score, score, "{:?}", password);
+        }
+    }
+
+    #[test]
+    fn warns_about_weaknesses() {
+        let cases = [
+            ("", None),
+            ("password", Some("This is one of the most common passwords")),
+            ("P@ssw0rd", Some("This is one of the most common passwords")),
+            ("dragonmonkey", Some("Common words and passwords are easy to guess")),
+            ("ggggggggggg", Some("Repeats and sequences like \"aaa\" or \"abc\" are easy to guess")),
+            ("ghijklmnopq", Some("Repeats and sequences like \"aaa\" or \"abc\" are easy to guess")),
+            ("k7#Qz", Some("Add more words or characters")),
+            ("x9$Lq2!vR7zP", None),
+        ];
+        for (password, warning) in cases {
+            assert_eq!(estimate(password).warning, warning, "{:?}", password);
+        }
+    }
+
+    #[test]
+    fn finds_common_passwords_with_substitutions() {
+        assert_eq!(common_rank("password"), Some(1));
+        assert_eq!(common_rank("PASSWORD"), Some(1));
+        assert_eq!(common_rank("p4$$w0rd"), Some(1));
+        assert_eq!(common_rank("x9$Lq2!vR7zP"), None);
+    }
+
+    #[test]
+    fn longer_passwords_are_stronger() {
+        let mut last = 0;
+        for len in 1..=16 {
+            let score = estimate(&"x9$Lq2!vR7zPm4&K"[..len]).score;
+            assert!(score >= last, "{}", len);
+            last = score;
+        }
+        assert_eq!(last, 4);
+    }
+}